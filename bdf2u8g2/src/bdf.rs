@@ -0,0 +1,161 @@
+//! Reading the glyphs of a BDF font.
+
+use crate::Error;
+
+/// Bounding box of a glyph, `x` and `y` are the offset of the lower left
+/// corner from the origin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub w: i32,
+    pub h: i32,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    pub encoding: u32,
+    /// Horizontal advance to the next glyph.
+    pub dwidth: i32,
+    pub bbx: BoundingBox,
+    /// `bbx.h` rows from the top, each `bbx.w` pixels from the left.
+    pub rows: Vec<Vec<bool>>,
+}
+
+impl Glyph {
+    /// Whether the pixel at `x`, `y` in font coordinates is set.
+    pub fn pixel(&self, x: i32, y: i32) -> bool {
+        let column = x - self.bbx.x;
+        let row = self.bbx.y + self.bbx.h - 1 - y;
+        if column < 0 || row < 0 || column >= self.bbx.w || row >= self.bbx.h {
+            return false;
+        }
+        self.rows[row as usize][column as usize]
+    }
+
+    /// Shrinks the bounding box to the set pixels, an empty glyph gets an
+    /// empty box at the origin.
+    pub fn reduce(&mut self) {
+        let set = |x: i32, y: i32| self.pixel(x, y);
+        let BoundingBox { w, h, x, y } = self.bbx;
+        let columns: Vec<i32> = (x..x + w)
+            .filter(|&c| (y..y + h).any(|r| set(c, r)))
+            .collect();
+        let lines: Vec<i32> = (y..y + h)
+            .filter(|&r| (x..x + w).any(|c| set(c, r)))
+            .collect();
+        let reduced = match (columns.first(), columns.last(), lines.first(), lines.last()) {
+            (Some(&left), Some(&right), Some(&bottom), Some(&top)) => BoundingBox {
+                w: right - left + 1,
+                h: top - bottom + 1,
+                x: left,
+                y: bottom,
+            },
+            _ => BoundingBox::default(),
+        };
+        self.rows = (0..reduced.h)
+            .map(|row| {
+                let y = reduced.y + reduced.h - 1 - row;
+                (reduced.x..reduced.x + reduced.w)
+                    .map(|x| self.pixel(x, y))
+                    .collect()
+            })
+            .collect();
+        self.bbx = reduced;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    /// Glyphs with an encoding, in file order.
+    pub glyphs: Vec<Glyph>,
+}
+
+impl Font {
+    pub fn glyph(&self, encoding: u32) -> Option<&Glyph> {
+        self.glyphs.iter().find(|glyph| glyph.encoding == encoding)
+    }
+}
+
+/// Parses a BDF font. Glyphs with an encoding of -1 are skipped.
+pub fn parse(bdf: &str) -> Result<Font, Error> {
+    let mut glyphs = Vec::new();
+    let mut lines = bdf.lines().enumerate();
+    while let Some((number, line)) = lines.next() {
+        if !line.starts_with("STARTCHAR") {
+            continue;
+        }
+        let error = |message: &str| Error::Bdf {
+            line: number + 1,
+            message: message.into(),
+        };
+        let mut encoding = None;
+        let mut dwidth = 0;
+        let mut bbx = None;
+        let mut rows = Vec::new();
+        let mut in_bitmap = false;
+        loop {
+            let Some((number, line)) = lines.next() else {
+                return Err(error("glyph without ENDCHAR"));
+            };
+            let error = |message: &str| Error::Bdf {
+                line: number + 1,
+                message: message.into(),
+            };
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            let mut number = || -> Result<i32, Error> {
+                words
+                    .next()
+                    .and_then(|word| word.parse().ok())
+                    .ok_or_else(|| error("expected a number"))
+            };
+            match keyword {
+                "ENDCHAR" => break,
+                _ if in_bitmap => {
+                    let bbx: BoundingBox = bbx.ok_or_else(|| error("BITMAP before BBX"))?;
+                    rows.push(parse_row(keyword, bbx.w).ok_or_else(|| error("bad bitmap row"))?);
+                }
+                "ENCODING" => encoding = Some(number()?),
+                "DWIDTH" => dwidth = number()?,
+                "BBX" => {
+                    bbx = Some(BoundingBox {
+                        w: number()?,
+                        h: number()?,
+                        x: number()?,
+                        y: number()?,
+                    })
+                }
+                "BITMAP" => in_bitmap = true,
+                _ => {}
+            }
+        }
+
+        let encoding = encoding.ok_or_else(|| error("glyph without ENCODING"))?;
+        let bbx = bbx.ok_or_else(|| error("glyph without BBX"))?;
+        if rows.len() != bbx.h as usize {
+            return Err(error("bitmap rows do not match the BBX height"));
+        }
+        if let Ok(encoding) = u32::try_from(encoding) {
+            glyphs.push(Glyph {
+                encoding,
+                dwidth,
+                bbx,
+                rows,
+            });
+        }
+    }
+    Ok(Font { glyphs })
+}
+
+/// A hex bitmap row, padded to whole bytes.
+fn parse_row(hex: &str, width: i32) -> Option<Vec<bool>> {
+    let bytes = hex
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    (0..width as usize)
+        .map(|x| bytes.get(x / 8).map(|byte| byte & (0x80 >> (x % 8)) != 0))
+        .collect()
+}
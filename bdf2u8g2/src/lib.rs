@@ -0,0 +1,121 @@
+//! Converts BDF bitmap fonts to the u8g2 font format, a Rust port of the
+//! parts of u8g2's `bdfconv` used for `u8g2_fonts`.
+//!
+//! ```text
+//! let bdf = std::fs::read_to_string("xkcd.bdf")?;
+//! let font = bdf2u8g2::convert(&bdf, "32,65-90", BuildMode::Proportional)?;
+//! std::fs::write("xkcd_font.rs", bdf2u8g2::rust_source("Xkcd", &font))?;
+//! ```
+//!
+//! Build scripts can convert all fonts of a manifest with `build_fonts`.
+
+pub mod bdf;
+pub mod manifest;
+pub mod map;
+pub mod u8g2;
+
+use std::path::Path;
+use std::{fmt, fs};
+
+pub use map::GlyphMap;
+pub use u8g2::BuildMode;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The BDF file is malformed at `line`.
+    Bdf { line: usize, message: String },
+    /// The glyph map cannot be parsed.
+    Map(String),
+    /// A glyph does not fit the u8g2 format.
+    Glyph { encoding: u32, message: String },
+    /// The font manifest is malformed at `line`.
+    Manifest { line: usize, message: String },
+    /// A file cannot be read or written.
+    Io { path: String, message: String },
+    /// The font in the BDF file at `path` cannot be converted.
+    Font { path: String, error: Box<Error> },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Bdf { line, message } => write!(f, "BDF line {line}: {message}"),
+            Error::Map(message) => write!(f, "glyph map: {message}"),
+            Error::Glyph { encoding, message } => write!(f, "glyph {encoding}: {message}"),
+            Error::Manifest { line, message } => write!(f, "manifest line {line}: {message}"),
+            Error::Io { path, message } => write!(f, "{path}: {message}"),
+            Error::Font { path, error } => write!(f, "{path}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Converts the BDF font in `bdf` to u8g2 font data with the glyphs
+/// selected by `map`, in `bdfconv -m` syntax.
+pub fn convert(bdf: &str, map: &str, mode: BuildMode) -> Result<Vec<u8>, Error> {
+    let font = bdf::parse(bdf)?;
+    let map = GlyphMap::parse(map)?;
+    u8g2::encode(&font, &map, mode)
+}
+
+/// Rust source of a unit struct `name` implementing `u8g2_fonts::Font`
+/// with `data`.
+pub fn rust_source(name: &str, data: &[u8]) -> String {
+    format!(
+        "use u8g2_fonts::Font;\n\n{}",
+        font_item(name, "Generated by bdf2u8g2, do not edit.", data)
+    )
+}
+
+/// The struct and `Font` impl of `rust_source`, documented with `doc`.
+fn font_item(name: &str, doc: &str, data: &[u8]) -> String {
+    let mut source = format!(
+        "/// {doc}\n\
+         pub struct {name};\n\
+         \n\
+         impl Font for {name} {{\n    const DATA: &'static [u8] = &[\n"
+    );
+    for line in data.chunks(16) {
+        source += "       ";
+        for byte in line {
+            source += &format!(" 0x{byte:02X},");
+        }
+        source += "\n";
+    }
+    source += "    ];\n}\n";
+    source
+}
+
+/// Converts every font listed in the manifest at `manifest` and writes
+/// their `Font` types to `out`. Meant for build scripts, it tells cargo to
+/// rerun when the manifest or a BDF file changes.
+pub fn build_fonts(manifest: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<(), Error> {
+    let manifest = manifest.as_ref();
+    let read = |path: &Path| {
+        println!("cargo:rerun-if-changed={}", path.display());
+        fs::read_to_string(path).map_err(|e| Error::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })
+    };
+    let fonts = manifest::parse(&read(manifest)?)?;
+    let dir = manifest.parent().unwrap_or(Path::new(""));
+
+    let mut source = String::from("use u8g2_fonts::Font;\n");
+    for font in fonts {
+        let bdf = dir.join(&font.bdf);
+        let data = convert(&read(&bdf)?, &font.map, font.mode).map_err(|e| Error::Font {
+            path: bdf.display().to_string(),
+            error: Box::new(e),
+        })?;
+        let doc = format!("`{}` with the glyphs `{}`.", font.bdf.display(), font.map);
+        source += "\n";
+        source += &font_item(&font.name, &doc, &data);
+    }
+    let out = out.as_ref();
+    fs::write(out, source).map_err(|e| Error::Io {
+        path: out.display().to_string(),
+        message: e.to_string(),
+    })
+}
@@ -0,0 +1,58 @@
+//! Converts a BDF font to a Rust `u8g2_fonts::Font`, with the options of
+//! u8g2's `bdfconv` it needs:
+//!
+//! ```text
+//! bdf2u8g2 -b 0 -m "32,65-90" -n Xkcd -o src/xkcd_font.rs src/bin/xkcd.bdf
+//! ```
+
+use std::{env, fs, process};
+
+use bdf2u8g2::BuildMode;
+
+fn main() {
+    let mut mode = BuildMode::Proportional;
+    let mut map = String::from("*");
+    let mut name = String::from("Font");
+    let mut output = None;
+    let mut input = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-b" => {
+                mode = args
+                    .next()
+                    .and_then(|number| number.parse().ok())
+                    .and_then(BuildMode::from_number)
+                    .unwrap_or_else(|| usage())
+            }
+            "-m" => map = args.next().unwrap_or_else(|| usage()),
+            "-n" => name = args.next().unwrap_or_else(|| usage()),
+            "-o" => output = Some(args.next().unwrap_or_else(|| usage())),
+            _ if input.is_none() && !arg.starts_with('-') => input = Some(arg),
+            _ => usage(),
+        }
+    }
+    let input = input.unwrap_or_else(|| usage());
+
+    let bdf =
+        fs::read_to_string(&input).unwrap_or_else(|e| fail(&format!("cannot read {input}: {e}")));
+    let font =
+        bdf2u8g2::convert(&bdf, &map, mode).unwrap_or_else(|e| fail(&format!("{input}: {e}")));
+    let source = bdf2u8g2::rust_source(&name, &font);
+    match output {
+        Some(path) => {
+            fs::write(&path, source).unwrap_or_else(|e| fail(&format!("cannot write {path}: {e}")))
+        }
+        None => print!("{source}"),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("usage: bdf2u8g2 [-b 0|1|2] [-m MAP] [-n NAME] [-o FILE.rs] FILE.bdf");
+    process::exit(2);
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    process::exit(1);
+}
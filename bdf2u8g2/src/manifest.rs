@@ -0,0 +1,61 @@
+//! Font manifests for build scripts, one font per line:
+//!
+//! ```text
+//! # name  mode  glyph map  BDF file
+//! Xkcd    0     32,65-90   src/bin/xkcd.bdf
+//! ```
+//!
+//! The mode is the `bdfconv -b` number, the map uses the `bdfconv -m`
+//! syntax without spaces and the BDF path is relative to the manifest.
+//! Everything after a `#` is a comment.
+
+use std::path::PathBuf;
+
+use crate::{BuildMode, Error};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontSpec {
+    /// Name of the generated `Font` type.
+    pub name: String,
+    pub mode: BuildMode,
+    pub map: String,
+    pub bdf: PathBuf,
+}
+
+pub fn parse(manifest: &str) -> Result<Vec<FontSpec>, Error> {
+    let mut fonts: Vec<FontSpec> = Vec::new();
+    for (number, line) in manifest.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let error = |message: &str| Error::Manifest {
+            line: number + 1,
+            message: message.into(),
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [name, mode, map, bdf] = fields[..] else {
+            if fields.is_empty() {
+                continue;
+            }
+            return Err(error("expected a name, a mode, a glyph map and a BDF file"));
+        };
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(error("the name must be a Rust identifier"));
+        }
+        if fonts.iter().any(|font| font.name == name) {
+            return Err(error("a font of that name is already listed"));
+        }
+        let mode = mode
+            .parse()
+            .ok()
+            .and_then(BuildMode::from_number)
+            .ok_or_else(|| error("the mode must be 0, 1 or 2"))?;
+        fonts.push(FontSpec {
+            name: name.into(),
+            mode,
+            map: map.into(),
+            bdf: bdf.into(),
+        });
+    }
+    Ok(fonts)
+}
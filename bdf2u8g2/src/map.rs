@@ -0,0 +1,101 @@
+//! Glyph maps in the syntax of `bdfconv -m`.
+//!
+//! A map is a comma separated list of commands, applied in order:
+//!
+//! - `*` selects every glyph,
+//! - `65-90` selects a range, a single number selects one glyph,
+//! - `65-90>97` selects a range and moves it to start at 97,
+//! - `~64` removes glyphs selected before.
+//!
+//! Numbers are decimal, or hex with a `$` prefix.
+
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    All,
+    Range { from: u32, to: u32, target: u32 },
+    Exclude { from: u32, to: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphMap {
+    commands: Vec<Command>,
+}
+
+impl GlyphMap {
+    pub fn parse(map: &str) -> Result<Self, Error> {
+        let commands = map
+            .split(',')
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+            .map(parse_command)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { commands })
+    }
+
+    /// Encoding the glyph `encoding` gets in the font, `None` if the map
+    /// does not select it.
+    pub fn map(&self, encoding: u32) -> Option<u32> {
+        let mut mapped = None;
+        for command in &self.commands {
+            match *command {
+                Command::All => mapped = Some(encoding),
+                Command::Range { from, to, target } if (from..=to).contains(&encoding) => {
+                    mapped = Some(encoding - from + target)
+                }
+                Command::Exclude { from, to } if (from..=to).contains(&encoding) => mapped = None,
+                _ => {}
+            }
+        }
+        mapped
+    }
+}
+
+fn parse_command(command: &str) -> Result<Command, Error> {
+    if command == "*" {
+        return Ok(Command::All);
+    }
+    if let Some(range) = command.strip_prefix('~') {
+        let (from, to) = parse_range(range)?;
+        return Ok(Command::Exclude { from, to });
+    }
+    let (range, target) = match command.split_once('>') {
+        Some((range, target)) => (range, Some(parse_number(target)?)),
+        None => (command, None),
+    };
+    let (from, to) = parse_range(range)?;
+    if target.is_some_and(|target| target.checked_add(to - from).is_none()) {
+        return Err(Error::Map(format!(
+            "{command} moves past the last encoding"
+        )));
+    }
+    Ok(Command::Range {
+        from,
+        to,
+        target: target.unwrap_or(from),
+    })
+}
+
+fn parse_range(range: &str) -> Result<(u32, u32), Error> {
+    let (from, to) = match range.split_once('-') {
+        Some((from, to)) => (parse_number(from)?, parse_number(to)?),
+        None => {
+            let number = parse_number(range)?;
+            (number, number)
+        }
+    };
+    if from > to {
+        return Err(Error::Map(format!("empty range {range}")));
+    }
+    Ok((from, to))
+}
+
+fn parse_number(number: &str) -> Result<u32, Error> {
+    let number = number.trim();
+    let parsed = match number.strip_prefix('$') {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => number.parse(),
+    };
+    parsed.map_err(|_| Error::Map(format!("bad number {number}")))
+}
@@ -0,0 +1,348 @@
+//! The u8g2 font format as written by `bdfconv -f 1`.
+//!
+//! A 23 byte header is followed by the glyphs up to 255, a 0 byte, a lookup
+//! table and the glyphs above 255. Each glyph is its encoding, its size in
+//! bytes and a bit stream of its bounding box, advance and run-length
+//! encoded pixels.
+
+use crate::bdf::{BoundingBox, Font, Glyph};
+use crate::map::GlyphMap;
+use crate::Error;
+
+/// How the glyph bounding boxes are stored, the `-b` option of `bdfconv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildMode {
+    /// Every glyph has its own box, the smallest font.
+    Proportional,
+    /// Every glyph has the height of the font.
+    CommonHeight,
+    /// Every glyph has the box and advance of the widest glyph.
+    Monospace,
+}
+
+impl BuildMode {
+    /// The mode for a `bdfconv -b` number.
+    pub fn from_number(number: u8) -> Option<Self> {
+        match number {
+            0 => Some(BuildMode::Proportional),
+            1 => Some(BuildMode::CommonHeight),
+            2 => Some(BuildMode::Monospace),
+            _ => None,
+        }
+    }
+
+    pub fn number(self) -> u8 {
+        match self {
+            BuildMode::Proportional => 0,
+            BuildMode::CommonHeight => 1,
+            BuildMode::Monospace => 2,
+        }
+    }
+}
+
+/// Glyphs before each entry of the lookup table for glyphs above 255.
+const UNICODE_BLOCK: usize = 100;
+
+/// Bit sizes of the glyph fields, shared by all glyphs of a font.
+#[derive(Debug, Clone, Copy, Default)]
+struct Fields {
+    width: u32,
+    height: u32,
+    x: u32,
+    y: u32,
+    dwidth: u32,
+    zeros: u32,
+    ones: u32,
+}
+
+/// Encodes the glyphs `map` selects from `font`.
+pub fn encode(font: &Font, map: &GlyphMap, mode: BuildMode) -> Result<Vec<u8>, Error> {
+    let mut glyphs: Vec<Glyph> = font
+        .glyphs
+        .iter()
+        .filter_map(|glyph| {
+            let mut glyph = glyph.clone();
+            glyph.encoding = map.map(glyph.encoding)?;
+            glyph.reduce();
+            Some(glyph)
+        })
+        .collect();
+    glyphs.sort_by_key(|glyph| glyph.encoding);
+    glyphs.dedup_by_key(|glyph| glyph.encoding);
+    if let Some(glyph) = glyphs
+        .iter()
+        .find(|glyph| glyph.encoding == 0 || glyph.encoding > 0xFFFF)
+    {
+        return Err(Error::Glyph {
+            encoding: glyph.encoding,
+            message: "u8g2 encodings are 1 to 65535".into(),
+        });
+    }
+
+    let max = max_box(&glyphs);
+    let max_dwidth = glyphs.iter().map(|glyph| glyph.dwidth).max().unwrap_or(0);
+    // The box each glyph is stored with, the bitmap keeps the reduced one.
+    let boxes: Vec<BoundingBox> = glyphs
+        .iter_mut()
+        .map(|glyph| match mode {
+            BuildMode::Proportional => glyph.bbx,
+            BuildMode::CommonHeight => BoundingBox {
+                y: max.y,
+                h: max.h,
+                ..glyph.bbx
+            },
+            BuildMode::Monospace => {
+                glyph.dwidth = max_dwidth;
+                max
+            }
+        })
+        .collect();
+
+    let mut fields = Fields::default();
+    for (glyph, bbx) in glyphs.iter().zip(&boxes) {
+        fields.width = fields.width.max(unsigned_bits(bbx.w));
+        fields.height = fields.height.max(unsigned_bits(bbx.h));
+        fields.x = fields.x.max(signed_bits(bbx.x));
+        fields.y = fields.y.max(signed_bits(bbx.y));
+        fields.dwidth = fields.dwidth.max(signed_bits(glyph.dwidth));
+    }
+    // Run length sizes giving the smallest font, the first ones on a tie.
+    let mut smallest = usize::MAX;
+    for zeros in 2..=9 {
+        for ones in 2..=7 {
+            let candidate = Fields {
+                zeros,
+                ones,
+                ..fields
+            };
+            let bits = glyphs
+                .iter()
+                .zip(&boxes)
+                .map(|(glyph, &bbx)| encode_glyph(glyph, bbx, candidate).len)
+                .sum();
+            if bits < smallest {
+                smallest = bits;
+                fields = candidate;
+            }
+        }
+    }
+
+    let mut ascii = Vec::new();
+    let mut unicode = Vec::new();
+    let mut position_upper_a = None;
+    let mut position_lower_a = None;
+    for (glyph, &bbx) in glyphs.iter().zip(&boxes) {
+        let bits = encode_glyph(glyph, bbx, fields).bytes;
+        let too_big = || Error::Glyph {
+            encoding: glyph.encoding,
+            message: "more than 255 bytes".into(),
+        };
+        if glyph.encoding <= 255 {
+            if glyph.encoding >= 'A' as u32 && position_upper_a.is_none() {
+                position_upper_a = Some(ascii.len());
+            }
+            if glyph.encoding >= 'a' as u32 && position_lower_a.is_none() {
+                position_lower_a = Some(ascii.len());
+            }
+            let size = u8::try_from(bits.len() + 2).map_err(|_| too_big())?;
+            ascii.extend([glyph.encoding as u8, size]);
+            ascii.extend(bits);
+        } else {
+            let size = u8::try_from(bits.len() + 3).map_err(|_| too_big())?;
+            let mut data = (glyph.encoding as u16).to_be_bytes().to_vec();
+            data.push(size);
+            data.extend(bits);
+            unicode.push((glyph.encoding as u16, data));
+        }
+    }
+    // The end of the glyphs up to 255, a glyph of size 0.
+    ascii.extend([0, 0]);
+    let position_unicode = ascii.len();
+
+    let header_glyph = |encoding: char| {
+        let mut glyph = font.glyph(encoding as u32)?.clone();
+        glyph.reduce();
+        Some(glyph.bbx)
+    };
+    let ascent = |bbx: Option<BoundingBox>| bbx.map_or(0, |bbx| bbx.y + bbx.h);
+    let descent = |bbx: Option<BoundingBox>| bbx.map_or(0, |bbx| bbx.y);
+    let position = |position: Option<usize>| {
+        u16::try_from(position.unwrap_or(0))
+            .map(u16::to_be_bytes)
+            .map_err(|_| Error::Map("glyphs up to 255 take more than 65535 bytes".into()))
+    };
+    let count =
+        u8::try_from(glyphs.len()).map_err(|_| Error::Map("more than 255 glyphs".into()))?;
+
+    let mut data = vec![
+        count,
+        mode.number(),
+        fields.zeros as u8,
+        fields.ones as u8,
+        fields.width as u8,
+        fields.height as u8,
+        fields.x as u8,
+        fields.y as u8,
+        fields.dwidth as u8,
+        max.w as u8,
+        max.h as u8,
+        max.x as u8,
+        max.y as u8,
+        ascent(header_glyph('A')) as u8,
+        descent(header_glyph('g')) as u8,
+        ascent(header_glyph('(')) as u8,
+        descent(header_glyph('(')) as u8,
+    ];
+    data.extend(position(position_upper_a)?);
+    data.extend(position(position_lower_a)?);
+    data.extend(position(Some(position_unicode))?);
+    data.extend(ascii);
+    data.extend(unicode_section(&unicode)?);
+    Ok(data)
+}
+
+/// Smallest box around the boxes of all glyphs.
+fn max_box(glyphs: &[Glyph]) -> BoundingBox {
+    let drawn = || glyphs.iter().map(|glyph| glyph.bbx).filter(|bbx| bbx.w > 0);
+    let (Some(left), Some(bottom)) = (
+        drawn().map(|bbx| bbx.x).min(),
+        drawn().map(|bbx| bbx.y).min(),
+    ) else {
+        return BoundingBox::default();
+    };
+    let right = drawn().map(|bbx| bbx.x + bbx.w).max().unwrap_or(left);
+    let top = drawn().map(|bbx| bbx.y + bbx.h).max().unwrap_or(bottom);
+    BoundingBox {
+        w: right - left,
+        h: top - bottom,
+        x: left,
+        y: bottom,
+    }
+}
+
+/// The lookup table for the glyphs above 255, each entry skips to the next
+/// block of glyphs and holds the last encoding of the block, then the
+/// glyphs and an end marker.
+fn unicode_section(glyphs: &[(u16, Vec<u8>)]) -> Result<Vec<u8>, Error> {
+    let blocks: Vec<&[(u16, Vec<u8>)]> = glyphs.chunks(UNICODE_BLOCK).collect();
+    let mut table = Vec::new();
+    let mut skip = 4 * blocks.len().max(1);
+    for (i, block) in blocks.iter().enumerate() {
+        let last = if i + 1 == blocks.len() {
+            0xFFFF
+        } else {
+            block[block.len() - 1].0
+        };
+        let entry = u16::try_from(skip).map_err(|_| {
+            Error::Map(format!(
+                "{UNICODE_BLOCK} glyphs above 255 take more than 65535 bytes"
+            ))
+        })?;
+        table.extend(entry.to_be_bytes());
+        table.extend(last.to_be_bytes());
+        skip = block.iter().map(|(_, data)| data.len()).sum();
+    }
+    if blocks.is_empty() {
+        table.extend([0x00, 0x04, 0xFF, 0xFF]);
+    }
+    table.extend(glyphs.iter().flat_map(|(_, data)| data.iter().copied()));
+    table.extend([0, 0]);
+    Ok(table)
+}
+
+/// Bits written least significant first.
+#[derive(Debug, Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    fn push(&mut self, size: u32, value: u32) {
+        for bit in 0..size {
+            if self.len % 8 == 0 {
+                self.bytes.push(0);
+            }
+            if value & (1 << bit) != 0 {
+                *self.bytes.last_mut().unwrap() |= 1 << (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+
+    fn push_signed(&mut self, size: u32, value: i32) {
+        self.push(size, (value + (1 << (size - 1))) as u32);
+    }
+}
+
+/// The bit stream of a glyph stored with the box `bbx`: the box, the
+/// advance and then the pixels row by row as pairs of a run of zeros and a
+/// run of ones. Each pair is followed by a 1 bit per repetition and a 0 bit.
+fn encode_glyph(glyph: &Glyph, bbx: BoundingBox, fields: Fields) -> Bits {
+    let mut bits = Bits::default();
+    bits.push(fields.width, bbx.w as u32);
+    bits.push(fields.height, bbx.h as u32);
+    bits.push_signed(fields.x, bbx.x);
+    bits.push_signed(fields.y, bbx.y);
+    bits.push_signed(fields.dwidth, glyph.dwidth);
+    if bbx.w == 0 {
+        return bits;
+    }
+
+    let mut runs = Vec::new();
+    let (mut zeros, mut ones) = (0, 0);
+    for y in (bbx.y..bbx.y + bbx.h).rev() {
+        for x in bbx.x..bbx.x + bbx.w {
+            if !glyph.pixel(x, y) {
+                if ones > 0 {
+                    runs.push((zeros, ones));
+                    (zeros, ones) = (0, 0);
+                }
+                zeros += 1;
+            } else {
+                ones += 1;
+            }
+        }
+    }
+    runs.push((zeros, ones));
+
+    let max_zeros = (1 << fields.zeros) - 1;
+    let max_ones = (1 << fields.ones) - 1;
+    let mut previous = None;
+    let mut pair = |bits: &mut Bits, run: (u32, u32)| {
+        if previous == Some(run) {
+            bits.push(1, 1);
+        } else {
+            if previous.is_some() {
+                bits.push(1, 0);
+            }
+            bits.push(fields.zeros, run.0);
+            bits.push(fields.ones, run.1);
+            previous = Some(run);
+        }
+    };
+    for (mut zeros, mut ones) in runs {
+        while zeros > max_zeros {
+            pair(&mut bits, (max_zeros, 0));
+            zeros -= max_zeros;
+        }
+        while ones > max_ones {
+            pair(&mut bits, (zeros, max_ones));
+            zeros = 0;
+            ones -= max_ones;
+        }
+        pair(&mut bits, (zeros, ones));
+    }
+    bits.push(1, 0);
+    bits
+}
+
+/// Bits needed for `value` as an unsigned field.
+fn unsigned_bits(value: i32) -> u32 {
+    32 - (value.max(0) as u32).leading_zeros()
+}
+
+/// Bits needed for `value` as a signed field.
+fn signed_bits(value: i32) -> u32 {
+    unsigned_bits(value.abs()) + 1
+}
@@ -0,0 +1,40 @@
+//! Fonts beyond what the u8g2 header can describe.
+
+use bdf2u8g2::{convert, BuildMode, Error};
+
+/// A font of 2x2 glyphs for `encodings`.
+fn font(encodings: impl Iterator<Item = u32>) -> String {
+    let mut bdf = String::from("STARTFONT 2.1\n");
+    for encoding in encodings {
+        bdf += &format!(
+            "STARTCHAR u{encoding}\nENCODING {encoding}\nDWIDTH 3 0\nBBX 2 2 0 0\n\
+             BITMAP\n80\n40\nENDCHAR\n"
+        );
+    }
+    bdf + "ENDFONT\n"
+}
+
+#[test]
+fn glyph_count() {
+    let bdf = font(1..=1000);
+    let font = convert(&bdf, "1-255", BuildMode::Proportional).unwrap();
+    assert_eq!(font[0], 255);
+    assert_eq!(
+        convert(&bdf, "1-256", BuildMode::Proportional),
+        Err(Error::Map("more than 255 glyphs".into()))
+    );
+    assert!(convert(&bdf, "1-1000", BuildMode::Proportional).is_err());
+}
+
+#[test]
+fn map_target_overflow() {
+    let bdf = font(1..=3);
+    assert_eq!(
+        convert(&bdf, "1-3>4294967295", BuildMode::Proportional),
+        Err(Error::Map(
+            "1-3>4294967295 moves past the last encoding".into()
+        ))
+    );
+    assert!(convert(&bdf, "1>4294967295", BuildMode::Proportional).is_err());
+    assert!(convert(&bdf, "1-3>$FFFFFFFD", BuildMode::Proportional).is_err());
+}
@@ -0,0 +1,66 @@
+//! Parsing font manifests.
+
+use std::fs;
+use std::path::Path;
+
+use bdf2u8g2::manifest::{parse, FontSpec};
+use bdf2u8g2::{build_fonts, BuildMode, Error};
+
+#[test]
+fn fonts_and_comments() {
+    let fonts = parse(
+        "# name mode map file\n\
+         \n\
+         Xkcd   0 32,65-90 src/bin/xkcd.bdf # the demo font\n\
+         Mono_8 2 32-126   fonts/mono.bdf\n",
+    )
+    .unwrap();
+    assert_eq!(
+        fonts,
+        [
+            FontSpec {
+                name: "Xkcd".into(),
+                mode: BuildMode::Proportional,
+                map: "32,65-90".into(),
+                bdf: "src/bin/xkcd.bdf".into(),
+            },
+            FontSpec {
+                name: "Mono_8".into(),
+                mode: BuildMode::Monospace,
+                map: "32-126".into(),
+                bdf: "fonts/mono.bdf".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn errors() {
+    let line = |manifest: &str| match parse(manifest) {
+        Err(Error::Manifest { line, .. }) => line,
+        other => panic!("expected a manifest error, got {other:?}"),
+    };
+    assert_eq!(line("Xkcd 0 32-90"), 1);
+    assert_eq!(line("\n1font 0 32-90 a.bdf"), 2);
+    assert_eq!(line("A 0 32 a.bdf\nA 0 33 b.bdf"), 2);
+    assert_eq!(line("A 4 32 a.bdf"), 1);
+}
+
+#[test]
+fn build_errors_name_the_font() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("build_errors");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("fonts.txt"), "Bad 0 3-1 bad.bdf\n").unwrap();
+    fs::write(dir.join("bad.bdf"), "STARTFONT 2.1\nENDFONT\n").unwrap();
+    let error = build_fonts(dir.join("fonts.txt"), dir.join("fonts.rs")).unwrap_err();
+    assert_eq!(
+        error,
+        Error::Font {
+            path: dir.join("bad.bdf").display().to_string(),
+            error: Box::new(Error::Map("empty range 3-1".into())),
+        }
+    );
+    assert!(error
+        .to_string()
+        .ends_with("bad.bdf: glyph map: empty range 3-1"));
+}
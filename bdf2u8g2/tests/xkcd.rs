@@ -0,0 +1,63 @@
+//! Checks the converter against `bdfconv` on the xkcd font of the epaper demo.
+
+use bdf2u8g2::{convert, BuildMode};
+
+/// The bytes of the C string literals in `source`, as `bdfconv` writes
+/// them, with the terminating 0 byte of the string.
+fn c_string(source: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let body = &source[source.find('=').unwrap()..];
+    for literal in body.split('"').skip(1).step_by(2) {
+        let mut chars = literal.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                bytes.push(c as u8);
+                continue;
+            }
+            let mut octal = String::new();
+            while octal.len() < 3 && chars.peek().is_some_and(|c| c.is_digit(8)) {
+                octal.push(chars.next().unwrap());
+            }
+            match octal.as_str() {
+                "" => bytes.push(chars.next().unwrap() as u8),
+                octal => bytes.push(u8::from_str_radix(octal, 8).unwrap()),
+            }
+        }
+    }
+    bytes.push(0);
+    bytes
+}
+
+#[test]
+fn matches_bdfconv() {
+    let bdf = include_str!("../../epaper/src/bin/xkcd.bdf");
+    let expected = c_string(include_str!("../../epaper/src/xkcd.c"));
+    assert_eq!(expected.len(), 372);
+
+    let font = convert(bdf, "32,65-90", BuildMode::Proportional).unwrap();
+    assert_eq!(font, expected);
+}
+
+#[test]
+fn map_moves_glyphs() {
+    let bdf = include_str!("../../epaper/src/bin/xkcd.bdf");
+    // Lower case letters in place of A to Z, without Q.
+    let font = convert(bdf, "65-90>97,~81", BuildMode::Proportional).unwrap();
+    assert_eq!(font[0], 25);
+    // No glyph from A up to a, the lower case ones start at the first glyph.
+    assert_eq!(font[17..21], [0, 0, 0, 0]);
+    assert_eq!(font[23], b'a');
+}
+
+#[test]
+fn monospace_glyphs() {
+    let bdf = include_str!("../../epaper/src/bin/xkcd.bdf");
+    let font = convert(bdf, "65-90", BuildMode::Monospace).unwrap();
+    assert_eq!(font[1], 2);
+    // Every glyph has the same width, height and advance, so the same fields.
+    let glyph = &font[23..];
+    assert_eq!(glyph[0], b'A');
+    let next = &glyph[glyph[1] as usize..];
+    assert_eq!(next[0], b'B');
+    assert_eq!(glyph[2] & 0x7F, next[2] & 0x7F);
+}
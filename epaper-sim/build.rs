@@ -0,0 +1,9 @@
+use std::{env, path::Path};
+
+/// Converts the fonts only the tests use, listed in `tests/fonts.txt`.
+fn main() {
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("fonts.rs");
+    if let Err(e) = bdf2u8g2::build_fonts("tests/fonts.txt", out) {
+        panic!("cannot build the test fonts: {e}");
+    }
+}
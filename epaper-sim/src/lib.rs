@@ -0,0 +1,177 @@
+//! Draws the epaper screens on the host, into the same display buffer and
+//! with the same rotation and offset as the firmware, and writes them as
+//! PNG or PBM images.
+
+use embedded_graphics::draw_target::{DrawTarget, DrawTargetExt};
+use embedded_graphics::geometry::{OriginDimensions, Point, Size};
+use epaper::dashboard_screen::DashboardScreen;
+use epaper::gag_screen::GagScreen;
+use epaper::pie_screen::PieScreen;
+use epaper::screen::Screen;
+use epd_waveshare::color::Color;
+use epd_waveshare::epd2in9_v2::{Display2in9, WIDTH};
+use epd_waveshare::graphics::DisplayRotation;
+
+/// Offset the firmware draws with, the top rows of the rotated display are
+/// not visible on the panel.
+pub const OFFSET: Point = Point::new(0, -12);
+
+/// A 2.9" display rotated by 90 degrees, 296 by 128 pixels.
+pub struct Frame {
+    display: Display2in9,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Frame {
+    /// A white frame.
+    pub fn new() -> Self {
+        let mut display = Display2in9::default();
+        display.set_rotation(DisplayRotation::Rotate90);
+        let _ = display.clear(Color::White);
+        Self { display }
+    }
+
+    /// The target the firmware's screens draw on.
+    pub fn target(&mut self) -> impl DrawTarget<Color = Color> + '_ {
+        self.display.translated(OFFSET)
+    }
+
+    /// Size of the rotated frame.
+    pub fn size(&self) -> Size {
+        self.display.size()
+    }
+
+    /// The panel buffer, as sent to the display.
+    pub fn buffer(&self) -> &[u8] {
+        self.display.buffer()
+    }
+
+    /// The pixel at `x`, `y` of the rotated frame.
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        // Rotate90 maps rotated `x`, `y` to panel column `WIDTH - 1 - y`, row `x`.
+        let column = WIDTH - 1 - y;
+        let byte = self.display.buffer()[(x * WIDTH.div_ceil(8) + column / 8) as usize];
+        if byte & (0x80 >> (column % 8)) != 0 {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    /// Rows of the rotated frame packed 8 pixels to a byte, most significant
+    /// bit first, with `black` for black pixels.
+    fn packed_rows(&self, black: bool) -> Vec<Vec<u8>> {
+        let Size { width, height } = self.size();
+        (0..height)
+            .map(|y| {
+                let mut row = vec![0; width.div_ceil(8) as usize];
+                for x in 0..width {
+                    if (self.pixel(x, y) == Color::Black) == black {
+                        row[(x / 8) as usize] |= 0x80 >> (x % 8);
+                    }
+                }
+                row
+            })
+            .collect()
+    }
+
+    /// The frame as a binary PBM (P4) image.
+    pub fn to_pbm(&self) -> Vec<u8> {
+        let Size { width, height } = self.size();
+        let mut pbm = format!("P4\n{width} {height}\n").into_bytes();
+        pbm.extend(self.packed_rows(true).concat());
+        pbm
+    }
+
+    /// The frame as a 1 bit greyscale PNG image.
+    pub fn to_png(&self) -> Vec<u8> {
+        let Size { width, height } = self.size();
+        let mut header = Vec::new();
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        // 1 bit greyscale, deflate, adaptive filtering, no interlacing.
+        header.extend([1, 0, 0, 0, 0]);
+
+        // Each row starts with filter type 0, white is 1.
+        let mut pixels = Vec::new();
+        for row in self.packed_rows(false) {
+            pixels.push(0);
+            pixels.extend(row);
+        }
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut png, b"IHDR", &header);
+        chunk(&mut png, b"IDAT", &zlib_stored(&pixels));
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+    png.extend(crc32(&[kind.as_slice(), data].concat()).to_be_bytes());
+}
+
+/// `data` as a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = data.chunks(0xFFFF).collect();
+    for (i, block) in blocks.iter().enumerate() {
+        zlib.push((i + 1 == blocks.len()) as u8);
+        let len = block.len() as u16;
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(*block);
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    zlib.extend(((b << 16) | a).to_be_bytes());
+    zlib
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Names of the screens `render` draws.
+pub const SCREENS: [&str; 3] = ["pie", "gag", "dashboard"];
+
+/// Draws the screen `name` like the firmware does, `None` for an unknown
+/// screen.
+pub fn render(name: &str) -> Option<Frame> {
+    match name {
+        "pie" => Some(draw(&mut PieScreen::new())),
+        "gag" => Some(draw(&mut GagScreen::new())),
+        "dashboard" => Some(draw(&mut DashboardScreen::new())),
+        _ => None,
+    }
+}
+
+/// Draws `screen` on a new frame.
+pub fn draw(screen: &mut impl Screen) -> Frame {
+    let mut frame = Frame::new();
+    // Drawing on the display buffer cannot fail.
+    let _ = screen.draw(&mut frame.target());
+    frame
+}
@@ -0,0 +1,31 @@
+//! Writes every screen of the epaper demo as PNG and PBM images:
+//!
+//! ```text
+//! cargo run -- frames
+//! ```
+
+use std::path::Path;
+use std::{env, fs, process};
+
+fn main() {
+    let dir = env::args().nth(1).unwrap_or_else(|| "frames".into());
+    let dir = Path::new(&dir);
+    if let Err(e) = fs::create_dir_all(dir) {
+        fail(&format!("cannot create {}: {e}", dir.display()));
+    }
+    for name in epaper_sim::SCREENS {
+        let frame = epaper_sim::render(name).expect("SCREENS are known screens");
+        for (extension, image) in [("png", frame.to_png()), ("pbm", frame.to_pbm())] {
+            let path = dir.join(format!("{name}.{extension}"));
+            if let Err(e) = fs::write(&path, image) {
+                fail(&format!("cannot write {}: {e}", path.display()));
+            }
+            println!("{}", path.display());
+        }
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    process::exit(1);
+}
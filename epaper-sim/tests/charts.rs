@@ -0,0 +1,128 @@
+//! Snapshots of the chart widgets, each drawn at two sizes, and checks that
+//! they stay inside their bounds.
+
+mod common;
+
+use embedded_graphics::mono_font::{ascii::FONT_6X10, MonoTextStyle};
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use epaper::chart::{BarChart, Datum, Gauge, LineChart, PieChart};
+use epaper::fonts::Xkcd;
+use epaper_sim::{Frame, OFFSET};
+use epd_waveshare::color::Color;
+use u8g2_fonts::U8g2TextStyle;
+
+use common::check;
+
+const DAYS: [Datum; 5] = [
+    Datum::new("MO", 3.0),
+    Datum::new("TU", 5.0),
+    Datum::new("WE", 0.5),
+    Datum::new("TH", 8.0),
+    Datum::new("FR", 4.0),
+];
+
+const SLICES: [Datum; 3] = [
+    Datum::new("RENT", 50.0),
+    Datum::new("FOOD", 30.0),
+    Datum::new("FUN", 20.0),
+];
+
+const VALUES: [f32; 12] = [3.0, 4.5, 4.0, 6.0, 5.5, 7.0, 2.0, 2.5, 5.0, 6.5, 6.0, 8.0];
+
+/// The visible area in the coordinates the firmware draws in.
+fn screen() -> Rectangle {
+    Rectangle::new(Point::zero() - OFFSET, Size::new(296, 128))
+}
+
+fn halves() -> [Rectangle; 2] {
+    let area = screen();
+    let left = Rectangle::new(area.top_left, Size::new(180, 128));
+    let right = Rectangle::new(area.top_left + Point::new(184, 32), Size::new(112, 64));
+    [left, right]
+}
+
+/// Draws `chart` for each of `halves`, checking nothing lands outside them.
+fn draw<C>(chart: impl Fn(Rectangle) -> C) -> Frame
+where
+    C: Drawable<Color = Color>,
+{
+    let mut frame = Frame::new();
+    for bounds in halves() {
+        let mut single = Frame::new();
+        let _ = chart(bounds).draw(&mut single.target());
+        let _ = chart(bounds).draw(&mut frame.target());
+        let size = single.size();
+        for y in 0..size.height {
+            for x in 0..size.width {
+                let point = Point::new(x as i32, y as i32) - OFFSET;
+                if single.pixel(x, y) == Color::Black {
+                    assert!(bounds.contains(point), "{point} outside {bounds:?}");
+                }
+            }
+        }
+    }
+    frame
+}
+
+#[test]
+fn pie() {
+    let style = U8g2TextStyle::new(Xkcd, Color::Black);
+    check(
+        "chart_pie",
+        &draw(|bounds| PieChart::new(bounds, &SLICES, style.clone())),
+    );
+    check(
+        "chart_donut",
+        &draw(|bounds| PieChart::new(bounds, &SLICES, style.clone()).with_hole(50)),
+    );
+}
+
+#[test]
+fn bar() {
+    let style = MonoTextStyle::new(&FONT_6X10, Color::Black);
+    check(
+        "chart_bar",
+        &draw(|bounds| BarChart::new(bounds, &DAYS, style)),
+    );
+}
+
+#[test]
+fn line() {
+    check(
+        "chart_line",
+        &draw(|bounds| LineChart::new(bounds, &VALUES).with_markers()),
+    );
+    check(
+        "chart_sparkline",
+        &draw(|bounds| LineChart::new(bounds, &VALUES).with_range(0.0, 10.0)),
+    );
+}
+
+#[test]
+fn gauge() {
+    let style = U8g2TextStyle::new(Xkcd, Color::Black);
+    check(
+        "chart_gauge",
+        &draw(|bounds| Gauge::new(bounds, 63.0, 0.0, 100.0, "63%", style.clone())),
+    );
+}
+
+#[test]
+fn empty() {
+    let style = MonoTextStyle::new(&FONT_6X10, Color::Black);
+    let blank = Frame::new().to_pbm();
+    let zero = [Datum::new("", 0.0)];
+    let frame = draw(|bounds| LineChart::new(bounds, &[]));
+    assert_eq!(frame.to_pbm(), blank);
+    // Nothing to divide, only the outline and the base line are drawn.
+    draw(|bounds| PieChart::new(bounds, &zero, style));
+    draw(|bounds| BarChart::new(bounds, &zero, style));
+    // More bars than pixels leaves only the base line.
+    let many = [Datum::new("", 1.0); 40];
+    let narrow = |bounds: Rectangle| Rectangle::new(bounds.top_left, Size::new(20, 64));
+    let frame = draw(|bounds| BarChart::new(narrow(bounds), &many, style));
+    let base = draw(|bounds| BarChart::new(narrow(bounds), &[], style));
+    assert_eq!(frame.to_pbm(), base.to_pbm());
+    draw(|bounds| Gauge::new(bounds, 0.0, 1.0, 1.0, "", style));
+}
@@ -0,0 +1,27 @@
+//! Helpers shared by the tests.
+
+use std::path::Path;
+use std::{env, fs};
+
+use epaper_sim::Frame;
+
+/// Compares `frame` with the snapshot `tests/snapshots/<name>.pbm`.
+pub fn check(name: &str, frame: &Frame) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots");
+    let path = dir.join(format!("{name}.pbm"));
+    let pbm = frame.to_pbm();
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &pbm).unwrap();
+        return;
+    }
+    let snapshot = fs::read(&path).unwrap_or_default();
+    if snapshot != pbm {
+        let new = dir.join(format!("{name}.new.pbm"));
+        fs::write(&new, &pbm).unwrap();
+        panic!(
+            "{name} differs from {}, see {}",
+            path.display(),
+            new.display()
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Draws every glyph of the converted fonts with `u8g2_fonts` and compares
+//! the pixels and advance with the BDF file.
+
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+
+use bdf2u8g2::{bdf, manifest, GlyphMap};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use u8g2_fonts::types::{FontColor, VerticalPosition};
+use u8g2_fonts::{Font, FontRenderer};
+
+/// Fonts converted by the build script from `tests/fonts.txt`.
+mod test_fonts {
+    include!(concat!(env!("OUT_DIR"), "/fonts.rs"));
+}
+
+const BDF: &str = include_str!("../../epaper/src/bin/xkcd.bdf");
+
+/// The points drawn to it.
+#[derive(Default)]
+struct Pixels(BTreeSet<Point>);
+
+impl OriginDimensions for Pixels {
+    fn size(&self) -> Size {
+        Size::new(256, 256)
+    }
+}
+
+impl DrawTarget for Pixels {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if color.is_on() {
+                self.0.insert(point);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draws every glyph `map` selects from the BDF with `F` on the baseline at
+/// the origin, returns how many there were.
+fn compare<F: Font>(map: &str) -> usize {
+    let renderer = FontRenderer::new::<F>();
+    let map = GlyphMap::parse(map).unwrap();
+    let mut drawn = 0;
+    for glyph in bdf::parse(BDF).unwrap().glyphs {
+        let Some(encoding) = map.map(glyph.encoding) else {
+            continue;
+        };
+        let c = char::from_u32(encoding).unwrap();
+        let mut pixels = Pixels::default();
+        let dimensions = renderer
+            .render(
+                c,
+                Point::zero(),
+                VerticalPosition::Baseline,
+                FontColor::Transparent(BinaryColor::On),
+                &mut pixels,
+            )
+            .unwrap_or_else(|_| panic!("{c:?} is missing"));
+        assert_eq!(dimensions.advance, Point::new(glyph.dwidth, 0), "{c:?}");
+
+        // Font coordinates go up from the baseline, the display's down.
+        let bbx = glyph.bbx;
+        let expected: BTreeSet<Point> = (bbx.x..bbx.x + bbx.w)
+            .flat_map(|x| (bbx.y..bbx.y + bbx.h).map(move |y| (x, y)))
+            .filter(|&(x, y)| glyph.pixel(x, y))
+            .map(|(x, y)| Point::new(x, -1 - y))
+            .collect();
+        assert_eq!(pixels.0, expected, "{c:?}");
+        drawn += 1;
+    }
+    drawn
+}
+
+/// Whether `F` has a glyph for `c`.
+fn has<F: Font>(c: char) -> bool {
+    FontRenderer::new::<F>()
+        .get_rendered_dimensions(c, Point::zero(), VerticalPosition::Baseline)
+        .is_ok()
+}
+
+#[test]
+fn xkcd() {
+    let fonts = manifest::parse(include_str!("../../epaper/fonts.txt")).unwrap();
+    let xkcd = fonts.iter().find(|font| font.name == "Xkcd").unwrap();
+    let drawn = compare::<epaper::fonts::Xkcd>(&xkcd.map);
+    assert_eq!(drawn, epaper::fonts::Xkcd::DATA[0] as usize);
+
+    // All of printable ASCII the BDF has, which lacks only the backtick.
+    for c in (' '..='~').filter(|&c| c != '`') {
+        assert!(has::<epaper::fonts::Xkcd>(c), "{c:?} is missing");
+    }
+    assert!(!has::<epaper::fonts::Xkcd>('`'));
+    // The Latin-1 glyphs, looked up past the one for 'a'.
+    for c in ['¦', '±', 'Å', 'É', 'Ò', 'Ü', '÷'] {
+        assert!(has::<epaper::fonts::Xkcd>(c), "{c:?} is missing");
+    }
+    assert!(!has::<epaper::fonts::Xkcd>('Σ'));
+}
+
+#[test]
+fn unicode() {
+    let drawn = compare::<test_fonts::XkcdAll>("*");
+    assert_eq!(drawn, test_fonts::XkcdAll::DATA[0] as usize);
+    // Glyphs above 255 are found through the lookup table.
+    for c in ['Σ', '‘', '≠', '≫'] {
+        assert!(has::<test_fonts::XkcdAll>(c), "{c:?} is missing");
+    }
+    assert!(!has::<test_fonts::XkcdAll>('\u{2000}'));
+}
@@ -0,0 +1,165 @@
+//! Parsing and placing declarative layouts.
+
+use embedded_graphics::image::ImageRaw;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use epaper::dashboard_screen::{DASHBOARD, RESOURCES};
+use epaper::layout::{ErrorKind, Layout, LayoutError, NamedFont, NamedImage, Resources};
+use epaper_sim::Frame;
+use epd_waveshare::color::Color;
+use u8g2_fonts::fonts::u8g2_font_6x10_tf;
+
+const SQUARE: [u8; 8] = [0xFF; 8];
+
+const RESOURCES_8X8: Resources = Resources {
+    fonts: &[NamedFont::new("small", u8g2_font_6x10_tf)],
+    images: &[NamedImage::new("square", ImageRaw::new(&SQUARE, 8))],
+};
+
+fn bounds() -> Rectangle {
+    Rectangle::new(Point::zero(), Size::new(200, 100))
+}
+
+fn parse(source: &str) -> Result<Layout<'_, 16>, LayoutError<'_>> {
+    Layout::parse(source, RESOURCES_8X8)
+}
+
+fn area(layout: &Layout<16>, id: &str) -> (i32, i32, u32, u32) {
+    let area = layout.area(id, bounds()).unwrap();
+    (
+        area.top_left.x,
+        area.top_left.y,
+        area.size.width,
+        area.size.height,
+    )
+}
+
+#[test]
+fn dashboard_parses() {
+    let layout = Layout::<16>::parse(DASHBOARD, RESOURCES).unwrap();
+    let screen = Rectangle::new(Point::new(0, 12), Size::new(296, 128));
+    let clock = layout.area("clock", screen).unwrap();
+    assert!(clock.size.width > 0 && clock.size.height == 20);
+}
+
+#[test]
+fn row_shares_space() {
+    let layout = parse(
+        "row padding=10 gap=4\n\
+         \x20 space id=a size=20\n\
+         \x20 space id=b\n\
+         \x20 space id=c grow=3\n",
+    )
+    .unwrap();
+    // 180 wide inside the padding, 20 fixed and 8 of gaps leave 152.
+    assert_eq!(area(&layout, "a"), (10, 10, 20, 80));
+    assert_eq!(area(&layout, "b"), (34, 10, 38, 80));
+    assert_eq!(area(&layout, "c"), (76, 10, 114, 80));
+}
+
+#[test]
+fn column_aligns_fixed_children() {
+    let layout = parse(
+        "column align=end gap=2\n\
+         \x20 space id=a size=10\n\
+         \x20 space id=b size=20 padding=1\n",
+    )
+    .unwrap();
+    assert_eq!(area(&layout, "a"), (0, 68, 200, 10));
+    assert_eq!(area(&layout, "b"), (1, 81, 198, 18));
+}
+
+#[test]
+fn nesting_and_stack() {
+    let layout = parse(
+        "# comment\n\
+         column\n\
+         \x20 row size=50\n\
+         \x20   stack id=stack\n\
+         \x20     space id=under\n\
+         \x20     image id=over name=square align=center valign=middle\n\
+         \x20   space\n\
+         \n\
+         \x20 space id=bottom\n",
+    )
+    .unwrap();
+    assert_eq!(area(&layout, "stack"), (0, 0, 100, 50));
+    assert_eq!(area(&layout, "under"), (0, 0, 100, 50));
+    assert_eq!(area(&layout, "over"), (0, 0, 100, 50));
+    assert_eq!(area(&layout, "bottom"), (0, 50, 200, 50));
+    assert!(layout.area("missing", bounds()).is_none());
+
+    let mut frame = Frame::new();
+    let _ = layout.draw(bounds(), &mut frame.target());
+    // The image is centred in the stack, drawn with the firmware's offset.
+    assert_eq!(frame.pixel(46, 20 - 12), Color::White);
+    assert_eq!(frame.pixel(46, 21 - 12), Color::Black);
+    assert_eq!(frame.pixel(53, 28 - 12), Color::Black);
+    assert_eq!(frame.pixel(54, 28 - 12), Color::White);
+}
+
+#[test]
+fn errors() {
+    let error = |source| parse(source).unwrap_err();
+    let check = |source, line, kind, token| {
+        assert_eq!(error(source), LayoutError { line, kind, token }, "{source}");
+    };
+    check("", 0, ErrorKind::Empty, "");
+    check("row\nrow", 2, ErrorKind::SecondRoot, "row");
+    check("text: hi\n  space", 2, ErrorKind::NotAContainer, "space");
+    check("row\n\tspace", 2, ErrorKind::Tab, "space");
+    check("grid", 1, ErrorKind::UnknownNode, "grid");
+    check(
+        "row colour=red",
+        1,
+        ErrorKind::UnknownAttribute,
+        "colour=red",
+    );
+    check("row gap=-1", 1, ErrorKind::BadValue, "gap=-1");
+    check(
+        "column padding=3000000000",
+        1,
+        ErrorKind::BadValue,
+        "padding=3000000000",
+    );
+    check(
+        "row\n  space size=65536",
+        2,
+        ErrorKind::BadValue,
+        "size=65536",
+    );
+    check("row align=up", 1, ErrorKind::BadValue, "align=up");
+    check("text font=big: hi", 1, ErrorKind::UnknownFont, "big");
+    check("image name=logo", 1, ErrorKind::UnknownImage, "logo");
+    check("bar: A 1, B x", 1, ErrorKind::BadContent, "A 1, B x");
+    check("line: 1 2 three", 1, ErrorKind::BadContent, "1 2 three");
+
+    let many = "column\n".to_string() + &"  space\n".repeat(16);
+    assert_eq!(error(&many).kind, ErrorKind::TooManyNodes);
+    assert_eq!(error("row gap=x").to_string(), "line 1: bad value 'gap=x'");
+}
+
+#[test]
+fn largest_values() {
+    let layout = parse(
+        "row padding=65535 gap=65535\n\
+         \x20 space id=a size=65535\n\
+         \x20 space id=b grow=65535\n\
+         \x20 column id=c grow=65535 gap=65535\n\
+         \x20   space size=65535\n",
+    )
+    .unwrap();
+    // Nothing is left inside the padding, b follows a and the gap.
+    assert_eq!(area(&layout, "b"), (3 * 65535, 65535, 0, 0));
+    let _ = layout.draw(bounds(), &mut Frame::new().target());
+}
+
+#[test]
+fn text_without_fonts() {
+    let none = Resources {
+        fonts: &[],
+        images: &[],
+    };
+    let error = Layout::<4>::parse("text: hi", none).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::UnknownFont);
+}
@@ -0,0 +1,81 @@
+//! Refresh decisions for frames drawn like the firmware draws them.
+
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use epaper::refresh::{Refresh, RefreshManager};
+use epaper_sim::{render, Frame};
+use epd_waveshare::color::Color;
+use epd_waveshare::epd2in9_v2::{HEIGHT, WIDTH};
+
+const BUFFER: usize = WIDTH.div_ceil(8) as usize * HEIGHT as usize;
+
+fn show(manager: &mut RefreshManager<BUFFER>, frame: &Frame) -> Refresh {
+    let plan = manager.plan(frame.buffer());
+    manager.shown(frame.buffer(), plan);
+    plan
+}
+
+fn with_box(top_left: Point) -> Frame {
+    let mut frame = Frame::new();
+    let _ = Rectangle::new(top_left, Size::new(10, 4))
+        .into_styled(PrimitiveStyle::with_fill(Color::Black))
+        .draw(&mut frame.target());
+    frame
+}
+
+#[test]
+fn first_frame_is_full() {
+    let mut manager = RefreshManager::<BUFFER>::new(WIDTH);
+    assert_eq!(show(&mut manager, &Frame::new()), Refresh::Full);
+    assert_eq!(show(&mut manager, &Frame::new()), Refresh::None);
+}
+
+#[test]
+fn small_change_is_quick() {
+    let mut manager = RefreshManager::<BUFFER>::new(WIDTH);
+    show(&mut manager, &Frame::new());
+
+    // Rotated x, y is panel column WIDTH - 1 - y, row x, after the offset.
+    let frame = with_box(Point::new(100, 52));
+    let changed = Rectangle::new(Point::new(WIDTH as i32 - 1 - 43, 100), Size::new(4, 10));
+    assert_eq!(manager.changed(frame.buffer()), Some(changed));
+    assert_eq!(show(&mut manager, &frame), Refresh::Quick(changed));
+    assert_eq!(manager.quick_count(), 1);
+    assert_eq!(manager.previous(), frame.buffer());
+}
+
+#[test]
+fn screen_switch_is_full() {
+    let mut manager = RefreshManager::<BUFFER>::new(WIDTH);
+    show(&mut manager, &render("pie").unwrap());
+    assert_eq!(show(&mut manager, &render("gag").unwrap()), Refresh::Full);
+}
+
+#[test]
+fn full_after_quick_ones() {
+    let mut manager = RefreshManager::<BUFFER>::new(WIDTH).with_full_every(3);
+    show(&mut manager, &Frame::new());
+    for i in 0..3 {
+        let frame = with_box(Point::new(100 + 20 * i, 52));
+        assert!(matches!(show(&mut manager, &frame), Refresh::Quick(_)));
+    }
+    assert_eq!(
+        show(&mut manager, &with_box(Point::new(0, 100))),
+        Refresh::Full
+    );
+    assert_eq!(manager.quick_count(), 0);
+    assert!(matches!(
+        show(&mut manager, &with_box(Point::new(20, 100))),
+        Refresh::Quick(_)
+    ));
+}
+
+#[test]
+fn quick_area_limit() {
+    let mut manager = RefreshManager::<BUFFER>::new(WIDTH).with_quick_area(39);
+    show(&mut manager, &Frame::new());
+    assert_eq!(
+        show(&mut manager, &with_box(Point::new(100, 52))),
+        Refresh::Full
+    );
+}
@@ -0,0 +1,74 @@
+//! The state kept between deep sleeps.
+
+use std::mem::MaybeUninit;
+
+use epaper::app::App;
+use epaper::refresh::{Refresh, RefreshManager};
+use epaper::screen::Screen;
+use epaper::sleep::Retained;
+use epaper_sim::Frame;
+
+const SHOWN: [u8; 32] = [0xFF; 32];
+
+/// A state filled with `byte`, like RTC memory after a reset interrupted
+/// its first zeroing.
+fn filled(byte: u8) -> Retained<32> {
+    let mut memory = MaybeUninit::<Retained<32>>::uninit();
+    // SAFETY: `Retained` holds only integers, valid for any bit pattern.
+    unsafe {
+        memory.as_mut_ptr().write_bytes(byte, 1);
+        memory.assume_init()
+    }
+}
+
+#[test]
+fn kept_only_after_wakeup() {
+    let mut retained = Retained::<32>::new();
+    let (mut app, mut refresh) = retained.restore(true, 16);
+    assert_eq!(retained.wakes, 0, "nothing was saved");
+    assert_eq!(app.screen(), 0);
+    assert_eq!(refresh.plan(&SHOWN), Refresh::Full);
+    app.next();
+    let _ = app.draw(&mut Frame::new().target());
+    refresh.shown(&SHOWN, Refresh::Full);
+    refresh.shown(&SHOWN, Refresh::Quick(Default::default()));
+    retained.save(&app, &refresh);
+
+    let (app, refresh) = retained.restore(true, 16);
+    assert_eq!(retained.wakes, 1);
+    assert_eq!(app.screen(), 1);
+    assert_eq!(refresh.previous(), SHOWN);
+    assert_eq!(refresh.quick_count(), 1);
+    assert_eq!(refresh.plan(&SHOWN), Refresh::None);
+    retained.save(&app, &refresh);
+    let _ = retained.restore(true, 16);
+    assert_eq!(retained.wakes, 2);
+
+    let (app, refresh) = retained.restore(false, 16);
+    assert_eq!(retained.wakes, 0);
+    assert_eq!(app.screen(), 0);
+    assert_eq!(refresh.plan(&SHOWN), Refresh::Full);
+    // Starting over forgets the saved state until the next save.
+    let _ = retained.restore(true, 16);
+    assert_eq!(retained.wakes, 0);
+}
+
+#[test]
+fn any_memory_contents() {
+    for byte in [0x00, 0x01, 0x50, 0xA5, 0xFF] {
+        let mut retained = filled(byte);
+        let (app, refresh) = retained.restore(true, 16);
+        assert_eq!(retained.wakes, 0, "{byte:#x}");
+        assert_eq!(app.screen(), 0);
+        assert_eq!(refresh.plan(&SHOWN), Refresh::Full);
+    }
+    // Unknown screen numbers start over with the first screen.
+    assert_eq!(App::with_screen(7).screen(), 0);
+    // The quick refreshes before the sleep count towards the next full one.
+    let mut changed = SHOWN;
+    changed[0] = 0x7F;
+    let refresh = RefreshManager::<32>::new(16).with_shown(&SHOWN, 9);
+    assert!(matches!(refresh.plan(&changed), Refresh::Quick(_)));
+    let refresh = RefreshManager::<32>::new(16).with_shown(&SHOWN, 10);
+    assert_eq!(refresh.plan(&changed), Refresh::Full);
+}
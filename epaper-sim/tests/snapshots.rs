@@ -0,0 +1,59 @@
+//! Compares every screen with its PBM snapshot in `tests/snapshots`.
+//!
+//! After an intended change, rerun with `UPDATE_SNAPSHOTS=1` to rewrite the
+//! snapshots. A mismatch writes the new frame next to the snapshot as
+//! `<screen>.new.pbm`.
+
+mod common;
+
+use embedded_graphics::prelude::*;
+use epaper::app::App;
+use epaper::screen::Screen;
+use epaper_sim::{draw, render, Frame, SCREENS};
+use epd_waveshare::color::Color;
+
+use common::check;
+
+#[test]
+fn screens() {
+    for name in SCREENS {
+        check(name, &render(name).unwrap());
+    }
+}
+
+#[test]
+fn rotation_and_offset() {
+    let mut frame = Frame::new();
+    assert_eq!(frame.size(), Size::new(296, 128));
+    // The firmware draws 12 rows up, so its row 12 is the top row.
+    let _ = Pixel(Point::new(5, 12), Color::Black).draw(&mut frame.target());
+    let _ = Pixel(Point::new(295, 139), Color::Black).draw(&mut frame.target());
+    assert_eq!(frame.pixel(5, 0), Color::Black);
+    assert_eq!(frame.pixel(295, 127), Color::Black);
+    let black = (0..296)
+        .flat_map(|x| (0..128).map(move |y| (x, y)))
+        .filter(|&(x, y)| frame.pixel(x, y) == Color::Black)
+        .count();
+    assert_eq!(black, 2);
+}
+
+#[test]
+fn app_alternates() {
+    let mut app = App::new();
+    assert!(app.changed());
+    let pie = draw(&mut app);
+    assert!(!app.changed());
+    assert_eq!(pie.to_pbm(), render("pie").unwrap().to_pbm());
+
+    app.next();
+    assert!(app.changed());
+    assert_eq!(draw(&mut app).to_pbm(), render("gag").unwrap().to_pbm());
+    app.next();
+    assert_eq!(draw(&mut app).to_pbm(), pie.to_pbm());
+    assert!(!app.changed());
+}
+
+#[test]
+fn unknown_screen() {
+    assert!(render("nope").is_none());
+}
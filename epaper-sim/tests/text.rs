@@ -0,0 +1,153 @@
+//! Layout of text boxes: wrapping, alignment, ellipsis and mixed fonts.
+
+mod common;
+
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::Alignment;
+use epaper::fonts::Xkcd;
+use epaper::text::{Run, TextBox, VerticalAlignment};
+use epaper_sim::{Frame, OFFSET};
+use epd_waveshare::color::Color;
+use u8g2_fonts::fonts::{u8g2_font_6x10_tf, u8g2_font_helvB12_tf};
+use u8g2_fonts::FontRenderer;
+
+use common::check;
+
+const XKCD: FontRenderer = FontRenderer::new::<Xkcd>();
+const HELV: FontRenderer = FontRenderer::new::<u8g2_font_helvB12_tf>();
+const SMALL: FontRenderer = FontRenderer::new::<u8g2_font_6x10_tf>();
+
+const STORY: &str = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG WHILE THE CAT WATCHES";
+
+/// A box in the coordinates the firmware draws in.
+fn area(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+    Rectangle::new(Point::new(x, y) - OFFSET, Size::new(width, height))
+}
+
+/// The smallest rectangle around the black pixels, in the coordinates the
+/// firmware draws in.
+fn ink(frame: &Frame) -> Option<Rectangle> {
+    let size = frame.size();
+    let mut points = (0..size.height)
+        .flat_map(|y| (0..size.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| frame.pixel(x, y) == Color::Black)
+        .map(|(x, y)| Point::new(x as i32, y as i32) - OFFSET);
+    let first = points.next()?;
+    let (min, max) = points.fold((first, first), |(min, max), p| {
+        (min.component_min(p), max.component_max(p))
+    });
+    Some(Rectangle::with_corners(min, max))
+}
+
+fn draw(text: &TextBox) -> Frame {
+    let mut frame = Frame::new();
+    let _ = text.draw(&mut frame.target());
+    frame
+}
+
+#[test]
+fn wraps_at_spaces() {
+    let runs = [Run::new(STORY, &XKCD)];
+    let bounds = area(10, 10, 120, 110);
+    let text = TextBox::new(bounds, &runs);
+    assert!(text.fits());
+    let size = text.size();
+    assert!(size.width <= 120, "{size:?}");
+    let line = XKCD.get_default_line_height();
+    assert!(size.height > 3 * line, "{size:?}");
+
+    let ink = ink(&draw(&text)).unwrap();
+    assert!(bounds.contains(ink.top_left) && bounds.contains(ink.bottom_right().unwrap()));
+}
+
+#[test]
+fn breaks_long_words() {
+    let runs = [Run::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ", &SMALL)];
+    let text = TextBox::new(area(0, 0, 60, 100), &runs);
+    // 6 pixels a glyph, 10 glyphs a line.
+    assert_eq!(
+        text.size(),
+        Size::new(60, 3 * SMALL.get_default_line_height())
+    );
+}
+
+#[test]
+fn alignment() {
+    let runs = [Run::new("HI", &XKCD)];
+    let bounds = area(20, 20, 200, 80);
+    let placed = |alignment, vertical| {
+        let text = TextBox::new(bounds, &runs)
+            .with_alignment(alignment)
+            .with_vertical_alignment(vertical);
+        ink(&draw(&text)).unwrap()
+    };
+    let top_left = placed(Alignment::Left, VerticalAlignment::Top);
+    let middle = placed(Alignment::Center, VerticalAlignment::Middle);
+    let bottom_right = placed(Alignment::Right, VerticalAlignment::Bottom);
+
+    assert!(top_left.top_left.x - bounds.top_left.x < 4);
+    assert!(top_left.top_left.y - bounds.top_left.y < 8);
+    let right = bounds.bottom_right().unwrap();
+    assert!(right.x - bottom_right.bottom_right().unwrap().x < 4);
+    assert!(right.y - bottom_right.bottom_right().unwrap().y < 8);
+    let (left, rest) = (
+        middle.top_left.x - bounds.top_left.x,
+        right.x - middle.bottom_right().unwrap().x,
+    );
+    assert!((left - rest).abs() <= 4, "{left} {rest}");
+}
+
+#[test]
+fn ellipsis() {
+    let runs = [Run::new(STORY, &XKCD)];
+    let bounds = area(10, 10, 120, 40);
+    let text = TextBox::new(bounds, &runs);
+    assert!(!text.fits());
+    let frame = draw(&text);
+    let ink = ink(&frame).unwrap();
+    assert!(bounds.contains(ink.top_left) && bounds.contains(ink.bottom_right().unwrap()));
+    check("text_ellipsis", &frame);
+
+    // Without the ellipsis only whole lines are drawn.
+    let clipped = draw(&text.with_ellipsis(false));
+    assert_ne!(clipped.to_pbm(), frame.to_pbm());
+}
+
+#[test]
+fn line_spacing() {
+    let runs = [Run::new("ONE\nTWO", &XKCD)];
+    let text = TextBox::new(area(0, 0, 100, 100), &runs);
+    let spaced = text.clone().with_line_spacing(6);
+    assert_eq!(spaced.size().height, text.size().height + 6);
+    let tight = text.clone().with_line_spacing(-4);
+    assert_eq!(tight.size().height, text.size().height - 4);
+}
+
+#[test]
+fn mixed_fonts() {
+    let runs = [
+        Run::new("TODAY ", &XKCD),
+        Run::new("14:30", &HELV),
+        Run::new(
+            " meeting with the team about the new display layout",
+            &SMALL,
+        ),
+    ];
+    let mut frame = Frame::new();
+    let boxes = [
+        (area(4, 4, 140, 120), Alignment::Left),
+        (area(152, 4, 140, 120), Alignment::Center),
+    ];
+    for (bounds, alignment) in boxes {
+        let _ = bounds
+            .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+            .draw(&mut frame.target());
+        let _ = TextBox::new(bounds.offset(-2), &runs)
+            .with_alignment(alignment)
+            .with_vertical_alignment(VerticalAlignment::Middle)
+            .with_line_spacing(2)
+            .draw(&mut frame.target());
+    }
+    check("text_mixed", &frame);
+}
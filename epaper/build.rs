@@ -1,9 +1,20 @@
+use std::{env, path::Path};
+
 fn main() {
     linker_be_nice();
+    fonts();
     // make sure linkall.x is the last linker script (otherwise might cause problems with flip-link)
     println!("cargo:rustc-link-arg=-Tlinkall.x");
 }
 
+/// Converts the fonts listed in `fonts.txt` into `$OUT_DIR/fonts.rs`.
+fn fonts() {
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("fonts.rs");
+    if let Err(e) = bdf2u8g2::build_fonts("fonts.txt", out) {
+        panic!("cannot build the fonts: {e}");
+    }
+}
+
 fn linker_be_nice() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() > 1 {
@@ -0,0 +1,75 @@
+//! The screens of the epaper demo and which one is shown.
+
+use embedded_graphics::draw_target::DrawTarget;
+use epd_waveshare::color::Color;
+
+use crate::gag_screen::GagScreen;
+use crate::pie_screen::PieScreen;
+use crate::screen::Screen;
+
+/// Alternates between the pie chart and the gag, starting with the pie chart.
+#[derive(Debug, Default)]
+pub struct App {
+    pie: PieScreen,
+    gag: GagScreen,
+    showing_pie: bool,
+    switched: bool,
+}
+
+impl App {
+    pub const fn new() -> Self {
+        Self {
+            pie: PieScreen::new(),
+            gag: GagScreen::new(),
+            showing_pie: true,
+            switched: true,
+        }
+    }
+
+    /// The app showing `screen`, numbered as by `screen()`. Other numbers
+    /// start over with the pie chart.
+    pub const fn with_screen(screen: u8) -> Self {
+        Self {
+            showing_pie: screen != 1,
+            ..Self::new()
+        }
+    }
+
+    /// The screen shown, 0 for the pie chart and 1 for the gag.
+    pub fn screen(&self) -> u8 {
+        if self.showing_pie {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Switches to the other screen, shown on the next `draw`.
+    pub fn next(&mut self) {
+        self.showing_pie = !self.showing_pie;
+        self.switched = true;
+    }
+}
+
+impl Screen for App {
+    fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        self.switched = false;
+        if self.showing_pie {
+            self.pie.draw(target)
+        } else {
+            self.gag.draw(target)
+        }
+    }
+
+    fn changed(&self) -> bool {
+        self.switched
+            || if self.showing_pie {
+                self.pie.changed()
+            } else {
+                self.gag.changed()
+            }
+    }
+}
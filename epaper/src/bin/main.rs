@@ -6,17 +6,16 @@
     holding buffers for the duration of a data transfer."
 )]
 
+use core::ptr::addr_of_mut;
+use core::time::Duration;
+
 use embassy_executor::Spawner;
-use embassy_time::{Duration, Instant};
 use embedded_graphics::{
     // mono_font::MonoTextStyleBuilder,
     // pixelcolor::BinaryColor::On as Black,
     draw_target::DrawTargetExt,
-    mono_font::{MonoTextStyleBuilder, ascii::FONT_6X10},
     //pixelcolor::{BinaryColor::Off, Rgb888},
     prelude::*,
-    primitives::{Circle, PrimitiveStyleBuilder, Sector},
-    text::{Text},
 };
 use embedded_hal_bus::spi::ExclusiveDevice;
 use epd_waveshare::{epd2in9_v2::*, graphics::DisplayRotation, prelude::*};
@@ -25,17 +24,21 @@ use esp_hal::{
     clock::CpuClock,
     delay::Delay,
     gpio::{Input, InputConfig, Level, Output, OutputConfig, Pull},
+    rtc_cntl::{
+        sleep::{Ext0WakeupSource, TimerWakeupSource, WakeupLevel},
+        wakeup_cause, Rtc,
+    },
     spi::{
         master::{Config, Spi},
         Mode,
     },
+    system::SleepSource,
     time::Rate,
     timer::timg::TimerGroup,
 };
 use log::info;
-use u8g2_fonts::{FontRenderer, U8g2TextStyle, fonts};
 
-use epaper::xkcd_font::Xkcd;
+use epaper::{refresh::Refresh, screen::Screen, sleep::Retained};
 
 extern crate alloc;
 
@@ -43,6 +46,16 @@ extern crate alloc;
 // For more information see: <https://docs.espressif.com/projects/esp-idf/en/stable/esp32/api-reference/system/app_image_format.html#application-description>
 esp_bootloader_esp_idf::esp_app_desc!();
 
+const BUFFER: usize = (WIDTH as usize).div_ceil(8) * HEIGHT as usize;
+
+/// Time between two screens, the button on GPIO13 skips ahead.
+const UPDATE_EVERY: Duration = Duration::from_secs(15 * 60);
+
+// RTC fast memory keeps its contents in deep sleep, `persistent` zeroes it
+// after power on only and leaves it alone at every other boot.
+#[esp_hal::ram(rtc_fast, persistent)]
+static mut RETAINED: Retained<BUFFER> = Retained::new();
+
 #[esp_hal_embassy::main]
 async fn main(spawner: Spawner) {
     // generator version: 0.5.0
@@ -115,151 +128,48 @@ async fn main(spawner: Spawner) {
     info!("Clearing display\r\n");
     let _ = display.clear(Color::White);
 
-    let mut shifted_display = display.translated(Point::new(0, -12));
-
-    // let style = PrimitiveStyleBuilder::new()
-    //     .stroke_color(Color::Black)
-    //     .stroke_width(3)
-    //     .fill_color(Color::Black)
-    //     .build();
-
-    // let circle_diameter = 100;
-    // let circle_radius = circle_diameter / 2;
-    // Sector::new(
-    //     Point::new(width / 2 - circle_radius, -40 + height / 2),
-    //     circle_diameter as u32,
-    //     0.0.deg(),
-    //     -315.0.deg(),
-    // )
-    // .into_styled(style)
-    // .draw(&mut shifted_display)
-    // .unwrap();
-
-    //let _ = epd.sleep(&mut spi_dev, &mut delay);
-
-    // main loop
-
     let _ = spawner;
 
-    let bbox = &shifted_display.bounding_box();
-    let width = bbox.size.width as i32;
-    let height = bbox.size.height as i32;
-
-    let mut app = App::new(width, height);
-    app.draw_pie(&mut shifted_display);
-
-    let _ = epd.update_and_display_frame(&mut spi_dev, &display.buffer(), &mut delay);
-
-    // let mut last = Instant::now();
-
-    loop {
-        //info!("Hello world!\r\n");
-        // let now = Instant::now();
-        // if now - last >= Duration::from_secs(15) {
-        //     last = now;
-
-        //     app.draw(&mut display);
-        //     let _ = epd.update_and_display_frame(&mut spi_dev, &display.buffer(), &mut delay);
-
-        //     // ✅ do your task here
-        //     //info!("5 seconds elapsed!\r\n");
-        // }
-        delay.delay_millis(400);
-        //Timer::after(Duration::from_secs(1)).await;
+    let cause = wakeup_cause();
+    let woke = matches!(cause, SleepSource::Timer | SleepSource::Ext0);
+    // SAFETY: nothing else uses RETAINED.
+    let retained = unsafe { &mut *addr_of_mut!(RETAINED) };
+    let (mut app, mut refresh) = retained.restore(woke, WIDTH);
+    info!("Woken by {:?}, wakeup {}\r\n", cause, retained.wakes);
+    if woke {
+        app.next();
     }
 
-    // for inspiration have a look at the examples at https://github.com/esp-rs/esp-hal/tree/esp-hal-v1.0.0-rc.0/examples/src/bin
-}
-
-struct App {
-    pie: bool,
-    width: i32,
-    height: i32,
-}
-
-impl App {
-    pub fn new(width: i32, height: i32) -> Self {
-        Self {
-            pie: true,
-            width,
-            height,
+    // The top rows of the rotated display are not visible on the panel.
+    let _ = app.draw(&mut display.translated(Point::new(0, -12)));
+
+    let plan = refresh.plan(display.buffer());
+    match plan {
+        Refresh::None => {}
+        Refresh::Quick(area) => {
+            // The driver has no partial quick refresh for this panel, both
+            // whole frames are sent and the panel drives the changed pixels.
+            info!("Quick refresh, changes in {:?}\r\n", area);
+            let _ = epd.update_old_frame(&mut spi_dev, refresh.previous(), &mut delay);
+            let _ = epd.update_and_display_new_frame(&mut spi_dev, display.buffer(), &mut delay);
         }
-    }
-
-    pub fn draw<D>(&mut self, target: &mut D)
-    where
-        D: DrawTarget<Color = Color>,
-    {
-        let _ = target.clear(Color::White);
-        self.pie = !self.pie;
-        if self.pie {
-            self.draw_pie(target);
-        } else {
-            self.draw_gag(target);
+        Refresh::Full => {
+            // `Epd2in9::new` loaded the full refresh LUT on this boot.
+            info!("Full refresh\r\n");
+            let _ = epd.update_and_display_frame(&mut spi_dev, display.buffer(), &mut delay);
         }
     }
+    refresh.shown(display.buffer(), plan);
+    retained.save(&app, &refresh);
 
-    pub fn draw_pie<D>(&self, target: &mut D)
-    where
-        D: DrawTarget<Color = Color>,
-    {
-        let style = PrimitiveStyleBuilder::new()
-            .stroke_color(Color::Black)
-            .stroke_width(3)
-            .fill_color(Color::Black)
-            .build();
-
-        let circle_style = PrimitiveStyleBuilder::new()
-            .stroke_color(Color::Black)
-            .stroke_width(1)
-            .build();
-
-        let circle_diameter = 100;
-        let circle_radius = circle_diameter / 2;
-
-        let _ = Sector::new(
-            Point::new(self.width / 2 - circle_radius, -40 + self.height / 2),
-            circle_diameter as u32,
-            0.0.deg(),
-            -315.0.deg(),
-        )
-        .into_styled(style)
-        .draw(target);
+    // The panel keeps the image without power.
+    let _ = epd.sleep(&mut spi_dev, &mut delay);
 
-        let _ = Circle::new(
-            Point::new(self.width / 2 - circle_radius, -40 + self.height / 2),
-            circle_diameter as u32,
-        ).into_styled(circle_style)
-        .draw(target)
-        ;
+    info!("Sleeping for {} s\r\n", UPDATE_EVERY.as_secs());
+    let mut rtc = Rtc::new(peripherals.LPWR);
+    let timer = TimerWakeupSource::new(UPDATE_EVERY);
+    let button = Ext0WakeupSource::new(peripherals.GPIO13, WakeupLevel::High);
+    rtc.sleep_deep(&[&timer, &button]);
 
-        let character_style = U8g2TextStyle::new(Xkcd, Color::Black);
-
-        // Draw "Hello World" at position (10, 10)
-        let _ = Text::new("TIME TAKEN", Point::new(12, 50), &character_style).draw(target);
-        let _ = Text::new("FINDING", Point::new(12, 75), &character_style).draw(target);
-        let _ = Text::new("LETTERS", Point::new(12, 100), &character_style).draw(target);
-        let _ = Text::new("TIME TAKEN", Point::new(210, 75), &character_style).draw(target);
-        let _ = Text::new("RENDERING", Point::new(210, 100), &character_style).draw(target);
-        let _ = Text::new("PIE CHART", Point::new(210, 125), &character_style).draw(target);    
-    }
-
-    pub fn draw_gag<D>(&self, target: &mut D)
-    where
-        D: DrawTarget<Color = Color>,
-    {
-        // Create a text style
-        // let text_style = MonoTextStyleBuilder::new()
-        //     .font(&FONT_6X10)
-        //     .text_color(Color::Black)
-        //     .build();
-
-        //let xkcd_font = FontRenderer::new::<Xkcd>();
-
-        //let character_style = U8g2TextStyle::new(fonts::u8g2_font_ncenB14_tr, Color::Black);
-        let character_style = U8g2TextStyle::new(Xkcd, Color::Black);
-
-        // Draw "Hello World" at position (10, 10)
-        let _ = Text::new("HELLO WORLD", Point::new(60, 60), character_style).draw(target);
-    }
+    // for inspiration have a look at the examples at https://github.com/esp-rs/esp-hal/tree/esp-hal-v1.0.0-rc.0/examples/src/bin
 }
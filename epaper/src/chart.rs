@@ -0,0 +1,536 @@
+//! Charts for the monochrome display.
+//!
+//! Every chart is drawn inside the rectangle it is given, so the layout
+//! decides its size rather than pixel constants. Labels are drawn with any
+//! text style, such as a `U8g2TextStyle`, and may span several lines.
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::{
+        Arc, Circle, Line, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, Sector,
+        StrokeAlignment,
+    },
+    text::{renderer::TextRenderer, Alignment, Baseline, Text, TextStyleBuilder},
+};
+use epd_waveshare::color::Color;
+use micromath::F32Ext;
+
+/// Space between a chart and its labels.
+const GAP: u32 = 4;
+
+/// A labelled value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Datum<'a> {
+    pub label: &'a str,
+    pub value: f32,
+}
+
+impl<'a> Datum<'a> {
+    pub const fn new(label: &'a str, value: f32) -> Self {
+        Self { label, value }
+    }
+}
+
+/// A pie chart, or a donut with `with_hole`. Slices go clockwise and are
+/// alternately black and white, each labelled left or right of the pie at
+/// the height of its middle.
+#[derive(Debug, Clone)]
+pub struct PieChart<'a, S> {
+    bounds: Rectangle,
+    slices: &'a [Datum<'a>],
+    style: S,
+    start: f32,
+    hole: u32,
+}
+
+impl<'a, S> PieChart<'a, S>
+where
+    S: TextRenderer<Color = Color> + Clone,
+{
+    /// The first slice starts at the top.
+    pub const fn new(bounds: Rectangle, slices: &'a [Datum<'a>], style: S) -> Self {
+        Self {
+            bounds,
+            slices,
+            style,
+            start: -90.0,
+            hole: 0,
+        }
+    }
+
+    /// Starts the first slice at `degrees`, clockwise from 3 o'clock.
+    pub const fn with_start(mut self, degrees: f32) -> Self {
+        self.start = degrees;
+        self
+    }
+
+    /// Cuts a hole of `percent` of the pie's diameter out of its middle.
+    pub const fn with_hole(mut self, percent: u32) -> Self {
+        self.hole = percent;
+        self
+    }
+}
+
+impl<S> Drawable for PieChart<'_, S>
+where
+    S: TextRenderer<Color = Color> + Clone,
+{
+    type Color = Color;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let labels = self.slices.iter().map(|slice| slice.label);
+        let label_width = labels_size(&self.style, labels).width;
+        let margin = margin(label_width);
+        let diameter = self
+            .bounds
+            .size
+            .height
+            .min(self.bounds.size.width.saturating_sub(2 * margin));
+        let center = self.bounds.center();
+        let circle = Circle::with_center(center, diameter);
+        let radius = diameter as f32 / 2.0;
+
+        let total: f32 = self.slices.iter().map(|slice| slice.value.max(0.0)).sum();
+        if total <= 0.0 {
+            return circle.into_styled(outline(1)).draw(target);
+        }
+
+        let mut angle = self.start;
+        let mut black = true;
+        let count = self.slices.iter().filter(|slice| slice.value > 0.0).count();
+        for (i, slice) in self.slices.iter().filter(|s| s.value > 0.0).enumerate() {
+            let sweep = 360.0 * slice.value / total;
+            // The last slice would be black next to the black first one.
+            let fill = if black && !(i > 0 && i + 1 == count) {
+                Color::Black
+            } else {
+                Color::White
+            };
+            Sector::from_circle(circle, angle.deg(), sweep.deg())
+                .into_styled(filled(fill))
+                .draw(target)?;
+
+            if !slice.label.is_empty() {
+                let middle = (angle + sweep / 2.0).to_radians();
+                let (x, alignment) = if middle.cos() >= 0.0 {
+                    (center.x + (radius as u32 + GAP) as i32, Alignment::Left)
+                } else {
+                    (center.x - (radius as u32 + GAP) as i32, Alignment::Right)
+                };
+                let height = labels_size(&self.style, [slice.label]).height as i32;
+                let top = self.bounds.top_left.y + height / 2;
+                let bottom = self.bounds.top_left.y + self.bounds.size.height as i32 - height / 2;
+                let y = (center.y + (radius * middle.sin()) as i32).clamp(top, bottom.max(top));
+                label(
+                    slice.label,
+                    Point::new(x, y),
+                    alignment,
+                    Baseline::Middle,
+                    self.style.clone(),
+                )
+                .draw(target)?;
+            }
+
+            angle += sweep;
+            black = !black;
+        }
+
+        if self.hole > 0 {
+            Circle::with_center(center, diameter * self.hole.min(100) / 100)
+                .into_styled(filled(Color::White))
+                .draw(target)?;
+        }
+        Ok(())
+    }
+}
+
+/// Vertical black bars on a base line, labelled below.
+#[derive(Debug, Clone)]
+pub struct BarChart<'a, S> {
+    bounds: Rectangle,
+    bars: &'a [Datum<'a>],
+    style: S,
+    max: Option<f32>,
+}
+
+impl<'a, S> BarChart<'a, S>
+where
+    S: TextRenderer<Color = Color> + Clone,
+{
+    /// The tallest bar fills the chart.
+    pub const fn new(bounds: Rectangle, bars: &'a [Datum<'a>], style: S) -> Self {
+        Self {
+            bounds,
+            bars,
+            style,
+            max: None,
+        }
+    }
+
+    /// Scales the bars so `max` fills the chart.
+    pub const fn with_max(mut self, max: f32) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl<S> Drawable for BarChart<'_, S>
+where
+    S: TextRenderer<Color = Color> + Clone,
+{
+    type Color = Color;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let labels = self.bars.iter().map(|bar| bar.label);
+        let label_height = labels_size(&self.style, labels).height;
+        let margin = margin(label_height);
+        let Size { width, height } = self.bounds.size;
+        let plot_height = height.saturating_sub(margin);
+        if plot_height == 0 || width == 0 {
+            return Ok(());
+        }
+        let left = self.bounds.top_left.x;
+        let base = self.bounds.top_left.y + plot_height as i32 - 1;
+        Line::new(
+            Point::new(left, base),
+            Point::new(left + width as i32 - 1, base),
+        )
+        .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+        .draw(target)?;
+
+        if self.bars.is_empty() {
+            return Ok(());
+        }
+        let max = self
+            .max
+            .unwrap_or_else(|| self.bars.iter().map(|bar| bar.value).fold(0.0, f32::max));
+        let slot = width / self.bars.len() as u32;
+        if slot == 0 {
+            // More bars than pixels, none of them fits.
+            return Ok(());
+        }
+        let bar_width = (slot * 2 / 3).max(1);
+        for (i, bar) in self.bars.iter().enumerate() {
+            let x = left + (slot * i as u32) as i32;
+            let fraction = if max > 0.0 {
+                (bar.value / max).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let bar_height = ((plot_height - 1) as f32 * fraction).round() as u32;
+            Rectangle::new(
+                Point::new(
+                    x + ((slot - bar_width) / 2) as i32,
+                    base - bar_height as i32,
+                ),
+                Size::new(bar_width, bar_height),
+            )
+            .into_styled(PrimitiveStyle::with_fill(Color::Black))
+            .draw(target)?;
+
+            if !bar.label.is_empty() {
+                let bottom = self.bounds.top_left.y + height as i32;
+                label(
+                    bar.label,
+                    Point::new(x + slot as i32 / 2, bottom),
+                    Alignment::Center,
+                    Baseline::Bottom,
+                    self.style.clone(),
+                )
+                .draw(target)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A line through `values`, evenly spaced from left to right. Without
+/// markers and with the default stroke it makes a sparkline.
+#[derive(Debug, Clone)]
+pub struct LineChart<'a> {
+    bounds: Rectangle,
+    values: &'a [f32],
+    range: Option<(f32, f32)>,
+    stroke: u32,
+    markers: bool,
+}
+
+impl<'a> LineChart<'a> {
+    /// The lowest value is at the bottom, the highest at the top.
+    pub const fn new(bounds: Rectangle, values: &'a [f32]) -> Self {
+        Self {
+            bounds,
+            values,
+            range: None,
+            stroke: 1,
+            markers: false,
+        }
+    }
+
+    /// Puts `min` at the bottom and `max` at the top.
+    pub const fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub const fn with_stroke_width(mut self, width: u32) -> Self {
+        self.stroke = width;
+        self
+    }
+
+    /// Marks every value with a dot.
+    pub const fn with_markers(mut self) -> Self {
+        self.markers = true;
+        self
+    }
+}
+
+impl Drawable for LineChart<'_> {
+    type Color = Color;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        const MARKER: u32 = 5;
+
+        // Keep thick lines and markers inside the bounds.
+        let inset = if self.markers {
+            MARKER.max(self.stroke) / 2
+        } else {
+            self.stroke / 2
+        };
+        let area = self.bounds.offset(-(inset as i32));
+        if area.is_zero_sized() || self.values.is_empty() {
+            return Ok(());
+        }
+
+        let (min, max) = self.range.unwrap_or_else(|| {
+            let values = self.values.iter().copied();
+            (
+                values.clone().fold(f32::INFINITY, f32::min),
+                values.fold(f32::NEG_INFINITY, f32::max),
+            )
+        });
+        let span = if max > min { max - min } else { 1.0 };
+        let Size { width, height } = area.size;
+        let last = (self.values.len() - 1).max(1) as u32;
+        let point = |i: usize, value: f32| {
+            let x = if self.values.len() == 1 {
+                width / 2
+            } else {
+                (width - 1) * i as u32 / last
+            };
+            let fraction = ((value - min) / span).clamp(0.0, 1.0);
+            let y = ((height - 1) as f32 * (1.0 - fraction)).round() as u32;
+            area.top_left + Size::new(x, y)
+        };
+
+        let style = PrimitiveStyle::with_stroke(Color::Black, self.stroke);
+        let mut previous = point(0, self.values[0]);
+        for (i, &value) in self.values.iter().enumerate().skip(1) {
+            let next = point(i, value);
+            Line::new(previous, next).into_styled(style).draw(target)?;
+            previous = next;
+        }
+        if self.markers || self.values.len() == 1 {
+            for (i, &value) in self.values.iter().enumerate() {
+                Circle::with_center(point(i, value), MARKER)
+                    .into_styled(PrimitiveStyle::with_fill(Color::Black))
+                    .draw(target)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A half circle band filled from the left up to `value`, labelled below.
+#[derive(Debug, Clone)]
+pub struct Gauge<'a, S> {
+    bounds: Rectangle,
+    value: f32,
+    min: f32,
+    max: f32,
+    label: &'a str,
+    style: S,
+}
+
+impl<'a, S> Gauge<'a, S>
+where
+    S: TextRenderer<Color = Color> + Clone,
+{
+    /// A gauge from `min` to `max`.
+    pub const fn new(
+        bounds: Rectangle,
+        value: f32,
+        min: f32,
+        max: f32,
+        label: &'a str,
+        style: S,
+    ) -> Self {
+        Self {
+            bounds,
+            value,
+            min,
+            max,
+            label,
+            style,
+        }
+    }
+}
+
+impl<S> Drawable for Gauge<'_, S>
+where
+    S: TextRenderer<Color = Color> + Clone,
+{
+    type Color = Color;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let label_height = labels_size(&self.style, [self.label]).height;
+        let margin = margin(label_height);
+        let Size { width, height } = self.bounds.size;
+        let diameter = width.min(2 * height.saturating_sub(margin));
+        if diameter < 4 {
+            return Ok(());
+        }
+        let band = (diameter / 8).max(2);
+
+        // The half circle and its label, centred in the bounds.
+        let used = diameter / 2 + margin;
+        let top = self.bounds.top_left.y + ((height - used) / 2) as i32;
+        let circle = Circle::new(
+            Point::new(
+                self.bounds.top_left.x + ((width - diameter) / 2) as i32,
+                top,
+            ),
+            diameter,
+        );
+        let center = circle.center();
+        let fraction = if self.max > self.min {
+            ((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let band_style = PrimitiveStyleBuilder::new()
+            .stroke_color(Color::Black)
+            .stroke_width(band)
+            .stroke_alignment(StrokeAlignment::Inside)
+            .build();
+        if fraction > 0.0 {
+            Arc::from_circle(circle, 180.0.deg(), (180.0 * fraction).deg())
+                .into_styled(band_style)
+                .draw(target)?;
+        }
+
+        let outline = outline(1);
+        Arc::from_circle(circle, 180.0.deg(), 180.0.deg())
+            .into_styled(outline)
+            .draw(target)?;
+        Arc::with_center(center, diameter - 2 * band, 180.0.deg(), 180.0.deg())
+            .into_styled(outline)
+            .draw(target)?;
+        let (left, right) = (circle.top_left.x, circle.top_left.x + diameter as i32 - 1);
+        for x in [left, right - band as i32 + 1] {
+            Line::new(
+                Point::new(x, center.y),
+                Point::new(x + band as i32 - 1, center.y),
+            )
+            .into_styled(outline)
+            .draw(target)?;
+        }
+
+        if !self.label.is_empty() {
+            label(
+                self.label,
+                Point::new(center.x, center.y + GAP as i32),
+                Alignment::Center,
+                Baseline::Top,
+                self.style.clone(),
+            )
+            .draw(target)?;
+        }
+        Ok(())
+    }
+}
+
+/// Room taken by labels `extent` wide or high.
+fn margin(extent: u32) -> u32 {
+    if extent > 0 {
+        extent + GAP
+    } else {
+        0
+    }
+}
+
+fn outline(width: u32) -> PrimitiveStyle<Color> {
+    PrimitiveStyleBuilder::new()
+        .stroke_color(Color::Black)
+        .stroke_width(width)
+        .stroke_alignment(StrokeAlignment::Inside)
+        .build()
+}
+
+fn filled(fill: Color) -> PrimitiveStyle<Color> {
+    PrimitiveStyleBuilder::new()
+        .stroke_color(Color::Black)
+        .stroke_width(1)
+        .stroke_alignment(StrokeAlignment::Inside)
+        .fill_color(fill)
+        .build()
+}
+
+/// The widest and the tallest of `labels`, zero without labels.
+fn labels_size<'t, S>(style: &S, labels: impl IntoIterator<Item = &'t str>) -> Size
+where
+    S: TextRenderer,
+{
+    let mut size = Size::zero();
+    for text in labels.into_iter().filter(|text| !text.is_empty()) {
+        for line in text.lines() {
+            let metrics = style.measure_string(line, Point::zero(), Baseline::Top);
+            size.width = size.width.max(metrics.bounding_box.size.width);
+        }
+        let lines = text.lines().count() as u32;
+        size.height = size.height.max(lines * style.line_height());
+    }
+    size
+}
+
+/// `text` positioned so all its lines, not just the first, are aligned to
+/// `anchor` by `baseline`.
+fn label<S>(
+    text: &str,
+    anchor: Point,
+    alignment: Alignment,
+    baseline: Baseline,
+    style: S,
+) -> Text<'_, S>
+where
+    S: TextRenderer,
+{
+    let extra = (text.lines().count().max(1) - 1) as i32 * style.line_height() as i32;
+    let shift = match baseline {
+        Baseline::Top => 0,
+        Baseline::Middle => extra / 2,
+        Baseline::Bottom | Baseline::Alphabetic => extra,
+    };
+    let text_style = TextStyleBuilder::new()
+        .alignment(alignment)
+        .baseline(baseline)
+        .build();
+    Text::with_text_style(text, anchor - Point::new(0, shift), style, text_style)
+}
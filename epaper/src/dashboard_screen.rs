@@ -0,0 +1,74 @@
+use embedded_graphics::{image::ImageRaw, prelude::*};
+use epd_waveshare::color::Color;
+use u8g2_fonts::{
+    fonts::u8g2_font_6x10_tf, types::FontColor, types::VerticalPosition, FontRenderer,
+};
+
+use crate::fonts::Xkcd;
+use crate::layout::{Layout, NamedFont, NamedImage, Resources};
+use crate::screen::Screen;
+
+/// The layout of the screen, parsed when it is drawn.
+pub const DASHBOARD: &str = include_str!("dashboard.layout");
+
+/// A 16 by 16 sun.
+#[rustfmt::skip]
+const SUN: [u8; 32] = [
+    0x01, 0x80, 0x21, 0x84, 0x10, 0x08, 0x07, 0xE0,
+    0x0F, 0xF0, 0x1F, 0xF8, 0x1F, 0xF8, 0xDF, 0xFB,
+    0xDF, 0xFB, 0x1F, 0xF8, 0x1F, 0xF8, 0x0F, 0xF0,
+    0x07, 0xE0, 0x10, 0x08, 0x21, 0x84, 0x01, 0x80,
+];
+
+/// The fonts and images `DASHBOARD` uses.
+pub const RESOURCES: Resources = Resources {
+    fonts: &[
+        NamedFont::new("xkcd", Xkcd),
+        NamedFont::new("small", u8g2_font_6x10_tf),
+    ],
+    images: &[NamedImage::new("sun", ImageRaw::new(&SUN, 16))],
+};
+
+/// Weather and a few charts, laid out by `dashboard.layout`.
+#[derive(Debug, Default)]
+pub struct DashboardScreen {
+    drawn: bool,
+}
+
+impl DashboardScreen {
+    pub const fn new() -> Self {
+        Self { drawn: false }
+    }
+}
+
+impl Screen for DashboardScreen {
+    fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        target.clear(Color::White)?;
+        let bounds = target.bounding_box();
+        match Layout::<16>::parse(DASHBOARD, RESOURCES) {
+            Ok(layout) => layout.draw(bounds, target)?,
+            Err(error) => {
+                // Show what is wrong instead of a blank screen.
+                let font = FontRenderer::new::<u8g2_font_6x10_tf>();
+                let color = FontColor::Transparent(Color::Black);
+                let _ = font.render(
+                    format_args!("{error}"),
+                    bounds.top_left,
+                    VerticalPosition::Top,
+                    color,
+                    target,
+                );
+            }
+        }
+
+        self.drawn = true;
+        Ok(())
+    }
+
+    fn changed(&self) -> bool {
+        !self.drawn
+    }
+}
@@ -0,0 +1,41 @@
+use embedded_graphics::{prelude::*, text::Alignment};
+use epd_waveshare::color::Color;
+use u8g2_fonts::FontRenderer;
+
+use crate::fonts::Xkcd;
+use crate::screen::Screen;
+use crate::text::{Run, TextBox, VerticalAlignment};
+
+const XKCD: FontRenderer = FontRenderer::new::<Xkcd>();
+
+/// A greeting in the xkcd font, in the middle of the screen.
+#[derive(Debug, Default)]
+pub struct GagScreen {
+    drawn: bool,
+}
+
+impl GagScreen {
+    pub const fn new() -> Self {
+        Self { drawn: false }
+    }
+}
+
+impl Screen for GagScreen {
+    fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        target.clear(Color::White)?;
+        TextBox::new(target.bounding_box(), &[Run::new("HELLO WORLD", &XKCD)])
+            .with_alignment(Alignment::Center)
+            .with_vertical_alignment(VerticalAlignment::Middle)
+            .draw(target)?;
+
+        self.drawn = true;
+        Ok(())
+    }
+
+    fn changed(&self) -> bool {
+        !self.drawn
+    }
+}
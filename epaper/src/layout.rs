@@ -0,0 +1,633 @@
+//! Screens described in a small text format instead of drawn in code.
+//!
+//! Every line is a node, the lines indented below a container are its
+//! children, and `#` starts a comment line:
+//!
+//! ```text
+//! column padding=4 gap=4
+//!   row size=20
+//!     image name=sun size=20
+//!     text font=xkcd valign=middle: SUNNY
+//!   row gap=8
+//!     gauge min=-10 max=40 value=21: TEMP
+//!     bar font=small: MO 3, TU 5, WE 1
+//! ```
+//!
+//! A node is a name, `key=value` attributes and, after a `:`, its content.
+//!
+//! * `row` and `column` place their children side by side or below each
+//!   other, `gap` pixels apart. Children get `size` pixels, or share the
+//!   rest by their `grow` weight, 1 by default. When nothing grows, `align`
+//!   (`start`, `center` or `end`) places them.
+//! * `stack` draws its children on top of each other.
+//! * `text` draws its content in `font`, aligned by `align` (`left`,
+//!   `center`, `right`) and `valign` (`top`, `middle`, `bottom`), with
+//!   `spacing` extra pixels between lines.
+//! * `image` draws the image `name`, aligned by `align` and `valign`.
+//! * `pie` and `bar` chart their content, `LABEL VALUE` pairs separated by
+//!   commas. A pie takes `hole` and `start`, a bar chart `max`.
+//! * `line` charts its content, space separated values, taking `min` and
+//!   `max` together, `stroke` and `markers`.
+//! * `gauge` shows `value` from `min` to `max`, labelled with its content.
+//! * `space` leaves its area empty.
+//!
+//! Every node takes `padding` and an `id` to look up its area with
+//! `Layout::area`. Fonts default to the first of the `Resources`. Whole
+//! numbers go up to 65535.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use embedded_graphics::{
+    image::{Image, ImageRaw},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+    text::Alignment,
+};
+use epd_waveshare::color::Color;
+use u8g2_fonts::{Font, FontRenderer, U8g2TextStyle};
+
+use crate::chart::{BarChart, Datum, Gauge, LineChart, PieChart};
+use crate::text::{Run, TextBox, VerticalAlignment};
+
+/// Most values in a chart.
+pub const MAX_VALUES: usize = 32;
+
+/// Deepest nesting of nodes.
+const MAX_DEPTH: usize = 16;
+
+/// Largest number attribute, far beyond any display but small enough that
+/// adding up the sizes and gaps of a row cannot overflow.
+const MAX_NUMBER: u32 = u16::MAX as u32;
+
+/// A font layouts refer to by name.
+#[derive(Debug, Clone)]
+pub struct NamedFont<'a> {
+    pub name: &'a str,
+    renderer: FontRenderer,
+    style: U8g2TextStyle<Color>,
+}
+
+impl<'a> NamedFont<'a> {
+    pub const fn new<F: Font>(name: &'a str, font: F) -> Self {
+        Self {
+            name,
+            renderer: FontRenderer::new::<F>(),
+            style: U8g2TextStyle::new(font, Color::Black),
+        }
+    }
+}
+
+/// A 1 bit image layouts refer to by name, set bits are black.
+#[derive(Debug, Clone)]
+pub struct NamedImage<'a> {
+    pub name: &'a str,
+    pub image: ImageRaw<'a, BinaryColor>,
+}
+
+impl<'a> NamedImage<'a> {
+    pub const fn new(name: &'a str, image: ImageRaw<'a, BinaryColor>) -> Self {
+        Self { name, image }
+    }
+}
+
+/// The fonts and images a layout can use.
+#[derive(Debug, Clone, Copy)]
+pub struct Resources<'a> {
+    pub fonts: &'a [NamedFont<'a>],
+    pub images: &'a [NamedImage<'a>],
+}
+
+/// What is wrong with a layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Empty,
+    SecondRoot,
+    NotAContainer,
+    TooManyNodes,
+    TooDeep,
+    Tab,
+    UnknownNode,
+    UnknownAttribute,
+    BadValue,
+    UnknownFont,
+    UnknownImage,
+    BadContent,
+}
+
+/// A layout error at `line`, counting from 1, caused by `token`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutError<'a> {
+    pub line: usize,
+    pub kind: ErrorKind,
+    pub token: &'a str,
+}
+
+impl fmt::Display for LayoutError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self.kind {
+            ErrorKind::Empty => "no nodes",
+            ErrorKind::SecondRoot => "more than one top level node",
+            ErrorKind::NotAContainer => "only row, column and stack have children",
+            ErrorKind::TooManyNodes => "too many nodes",
+            ErrorKind::TooDeep => "nested too deep",
+            ErrorKind::Tab => "indented with a tab",
+            ErrorKind::UnknownNode => "unknown node",
+            ErrorKind::UnknownAttribute => "unknown attribute",
+            ErrorKind::BadValue => "bad value",
+            ErrorKind::UnknownFont => "unknown font",
+            ErrorKind::UnknownImage => "unknown image",
+            ErrorKind::BadContent => "bad content",
+        };
+        write!(f, "line {}: {message} '{}'", self.line, self.token)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Row,
+    Column,
+    Stack,
+    Text,
+    Image,
+    Pie,
+    Bar,
+    Line,
+    Gauge,
+    Space,
+}
+
+impl Kind {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "row" => Self::Row,
+            "column" => Self::Column,
+            "stack" => Self::Stack,
+            "text" => Self::Text,
+            "image" => Self::Image,
+            "pie" => Self::Pie,
+            "bar" => Self::Bar,
+            "line" => Self::Line,
+            "gauge" => Self::Gauge,
+            "space" => Self::Space,
+            _ => return None,
+        })
+    }
+
+    fn is_container(self) -> bool {
+        matches!(self, Self::Row | Self::Column | Self::Stack)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Start,
+    Center,
+    End,
+}
+
+impl Align {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "start" | "left" | "top" => Self::Start,
+            "center" | "middle" => Self::Center,
+            "end" | "right" | "bottom" => Self::End,
+            _ => return None,
+        })
+    }
+
+    /// Offset of something `free` pixels smaller than its area.
+    fn offset(self, free: u32) -> i32 {
+        match self {
+            Self::Start => 0,
+            Self::Center => (free / 2) as i32,
+            Self::End => free as i32,
+        }
+    }
+}
+
+/// A node with its attributes. The nodes of a layout are in document
+/// order, the children of a node follow it up to `end`.
+#[derive(Debug, Clone, Copy)]
+struct Node<'a> {
+    kind: Kind,
+    id: &'a str,
+    content: &'a str,
+    indent: usize,
+    end: usize,
+    size: Option<u32>,
+    grow: u32,
+    padding: u32,
+    gap: u32,
+    align: Align,
+    valign: Align,
+    spacing: i32,
+    font: usize,
+    image: usize,
+    hole: u32,
+    start: f32,
+    min: Option<f32>,
+    max: Option<f32>,
+    value: f32,
+    stroke: u32,
+    markers: bool,
+}
+
+impl Node<'_> {
+    const EMPTY: Self = Self {
+        kind: Kind::Space,
+        id: "",
+        content: "",
+        indent: 0,
+        end: 0,
+        size: None,
+        grow: 1,
+        padding: 0,
+        gap: 0,
+        align: Align::Start,
+        valign: Align::Start,
+        spacing: 0,
+        font: 0,
+        image: 0,
+        hole: 0,
+        start: -90.0,
+        min: None,
+        max: None,
+        value: 0.0,
+        stroke: 1,
+        markers: false,
+    };
+}
+
+/// A parsed layout of up to `N` nodes.
+#[derive(Debug, Clone)]
+pub struct Layout<'a, const N: usize> {
+    nodes: [Node<'a>; N],
+    resources: Resources<'a>,
+}
+
+impl<'a, const N: usize> Layout<'a, N> {
+    /// Parses `source`, looking its fonts and images up in `resources`.
+    pub fn parse(source: &'a str, resources: Resources<'a>) -> Result<Self, LayoutError<'a>> {
+        let mut nodes = [Node::EMPTY; N];
+        let mut len = 0;
+        let mut open = [0; MAX_DEPTH];
+        let mut depth = 0;
+        for (number, line) in source.lines().enumerate() {
+            let number = number + 1;
+            let error = |kind, token| LayoutError {
+                line: number,
+                kind,
+                token,
+            };
+            let text = line.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - line.trim_start().len();
+            if line[..indent].contains('\t') {
+                return Err(error(ErrorKind::Tab, text));
+            }
+
+            while depth > 0 && nodes[open[depth - 1]].indent >= indent {
+                depth -= 1;
+                nodes[open[depth]].end = len;
+            }
+            if depth == 0 && len > 0 {
+                return Err(error(ErrorKind::SecondRoot, text));
+            }
+            if depth > 0 && !nodes[open[depth - 1]].kind.is_container() {
+                return Err(error(ErrorKind::NotAContainer, text));
+            }
+            if len == N {
+                return Err(error(ErrorKind::TooManyNodes, text));
+            }
+            if depth == MAX_DEPTH {
+                return Err(error(ErrorKind::TooDeep, text));
+            }
+
+            nodes[len] =
+                parse_node(text, indent, &resources).map_err(|(kind, token)| error(kind, token))?;
+            open[depth] = len;
+            depth += 1;
+            len += 1;
+        }
+        if len == 0 {
+            return Err(LayoutError {
+                line: 0,
+                kind: ErrorKind::Empty,
+                token: "",
+            });
+        }
+        while depth > 0 {
+            depth -= 1;
+            nodes[open[depth]].end = len;
+        }
+        Ok(Self { nodes, resources })
+    }
+
+    /// The area inside the padding of the node `id` when the layout fills
+    /// `bounds`.
+    pub fn area(&self, id: &str, bounds: Rectangle) -> Option<Rectangle> {
+        let mut found = None;
+        let _ = self.place(0, bounds, &mut |node, area| {
+            if found.is_none() && node.id == id {
+                found = Some(area);
+            }
+            Ok::<_, Infallible>(())
+        });
+        found
+    }
+
+    /// Draws the layout filling `bounds`.
+    pub fn draw<D>(&self, bounds: Rectangle, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        self.place(0, bounds, &mut |node, area| {
+            self.draw_node(node, area, target)
+        })
+    }
+
+    /// Calls `f` with each node of the subtree at `index` and its area
+    /// inside the padding, parents first.
+    fn place<E>(
+        &self,
+        index: usize,
+        bounds: Rectangle,
+        f: &mut impl FnMut(&Node<'a>, Rectangle) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let node = &self.nodes[index];
+        let area = inset(bounds, node.padding);
+        f(node, area)?;
+
+        match node.kind {
+            Kind::Stack => {
+                for child in self.children(index) {
+                    self.place(child, area, f)?;
+                }
+            }
+            Kind::Row | Kind::Column => {
+                let row = node.kind == Kind::Row;
+                let length = if row {
+                    area.size.width
+                } else {
+                    area.size.height
+                };
+                let (mut count, mut fixed, mut weights) = (0u32, 0, 0);
+                for child in self.children(index) {
+                    let child = &self.nodes[child];
+                    count += 1;
+                    match child.size {
+                        Some(size) => fixed = size.saturating_add(fixed),
+                        None => weights += child.grow,
+                    }
+                }
+                let gaps = node.gap.saturating_mul(count.saturating_sub(1));
+                let mut free = length.saturating_sub(gaps.saturating_add(fixed));
+                let mut offset = if weights == 0 {
+                    node.align.offset(free)
+                } else {
+                    0
+                };
+
+                for child in self.children(index) {
+                    let child_node = &self.nodes[child];
+                    let size = child_node.size.unwrap_or_else(|| {
+                        if weights == 0 {
+                            return 0;
+                        }
+                        let share =
+                            (free as u64 * child_node.grow as u64 / weights as u64) as u32;
+                        free -= share;
+                        weights -= child_node.grow;
+                        share
+                    });
+                    let child_area = if row {
+                        Rectangle::new(
+                            area.top_left + Point::new(offset, 0),
+                            Size::new(size, area.size.height),
+                        )
+                    } else {
+                        Rectangle::new(
+                            area.top_left + Point::new(0, offset),
+                            Size::new(area.size.width, size),
+                        )
+                    };
+                    self.place(child, child_area, f)?;
+                    offset += (size + node.gap) as i32;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Indices of the children of the node at `index`.
+    fn children(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let end = self.nodes[index].end;
+        let mut next = index + 1;
+        core::iter::from_fn(move || {
+            if next >= end {
+                return None;
+            }
+            let child = next;
+            next = self.nodes[child].end;
+            Some(child)
+        })
+    }
+
+    fn draw_node<D>(&self, node: &Node<'a>, area: Rectangle, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let fonts = self.resources.fonts;
+        match node.kind {
+            Kind::Text => {
+                let runs = [Run::new(node.content, &fonts[node.font].renderer)];
+                let alignment = match node.align {
+                    Align::Start => Alignment::Left,
+                    Align::Center => Alignment::Center,
+                    Align::End => Alignment::Right,
+                };
+                let vertical = match node.valign {
+                    Align::Start => VerticalAlignment::Top,
+                    Align::Center => VerticalAlignment::Middle,
+                    Align::End => VerticalAlignment::Bottom,
+                };
+                TextBox::new(area, &runs)
+                    .with_alignment(alignment)
+                    .with_vertical_alignment(vertical)
+                    .with_line_spacing(node.spacing)
+                    .draw(target)
+            }
+            Kind::Image => {
+                let image = &self.resources.images[node.image].image;
+                let size = image.size();
+                let free = area.size.saturating_sub(size);
+                let position = area.top_left
+                    + Point::new(
+                        node.align.offset(free.width),
+                        node.valign.offset(free.height),
+                    );
+                Image::new(image, position).draw(&mut target.clipped(&area).color_converted())
+            }
+            Kind::Pie | Kind::Bar => {
+                let mut data = [Datum::new("", 0.0); MAX_VALUES];
+                let count = parse_data(node.content, &mut data).unwrap_or(0);
+                let style = fonts[node.font].style.clone();
+                if node.kind == Kind::Pie {
+                    PieChart::new(area, &data[..count], style)
+                        .with_start(node.start)
+                        .with_hole(node.hole)
+                        .draw(target)
+                } else {
+                    let chart = BarChart::new(area, &data[..count], style);
+                    match node.max {
+                        Some(max) => chart.with_max(max).draw(target),
+                        None => chart.draw(target),
+                    }
+                }
+            }
+            Kind::Line => {
+                let mut values = [0.0; MAX_VALUES];
+                let count = parse_values(node.content, &mut values).unwrap_or(0);
+                let mut chart =
+                    LineChart::new(area, &values[..count]).with_stroke_width(node.stroke);
+                if let (Some(min), Some(max)) = (node.min, node.max) {
+                    chart = chart.with_range(min, max);
+                }
+                if node.markers {
+                    chart = chart.with_markers();
+                }
+                chart.draw(target)
+            }
+            Kind::Gauge => {
+                let style = fonts[node.font].style.clone();
+                let (min, max) = (node.min.unwrap_or(0.0), node.max.unwrap_or(100.0));
+                Gauge::new(area, node.value, min, max, node.content, style).draw(target)
+            }
+            Kind::Row | Kind::Column | Kind::Stack | Kind::Space => Ok(()),
+        }
+    }
+}
+
+/// Parses a node line without its indentation.
+fn parse_node<'a>(
+    text: &'a str,
+    indent: usize,
+    resources: &Resources<'a>,
+) -> Result<Node<'a>, (ErrorKind, &'a str)> {
+    let (head, content) = match text.split_once(':') {
+        Some((head, content)) => (head, content.trim()),
+        None => (text, ""),
+    };
+    let mut tokens = head.split_whitespace();
+    let name = tokens.next().unwrap_or(head);
+    let kind = Kind::from_name(name).ok_or((ErrorKind::UnknownNode, name))?;
+    let mut node = Node {
+        kind,
+        content,
+        indent,
+        ..Node::EMPTY
+    };
+
+    for token in tokens {
+        let (key, value) = token.split_once('=').unwrap_or((token, ""));
+        let bad = (ErrorKind::BadValue, token);
+        let number = || match value.parse::<u32>() {
+            Ok(number) if number <= MAX_NUMBER => Ok(number),
+            _ => Err(bad),
+        };
+        let real = || value.parse::<f32>().map_err(|_| bad);
+        match key {
+            "id" => node.id = value,
+            "size" => node.size = Some(number()?),
+            "grow" => node.grow = number()?,
+            "padding" => node.padding = number()?,
+            "gap" => node.gap = number()?,
+            "align" => node.align = Align::from_name(value).ok_or(bad)?,
+            "valign" => node.valign = Align::from_name(value).ok_or(bad)?,
+            "spacing" => node.spacing = value.parse().map_err(|_| bad)?,
+            "font" => {
+                node.font = resources
+                    .fonts
+                    .iter()
+                    .position(|font| font.name == value)
+                    .ok_or((ErrorKind::UnknownFont, value))?;
+            }
+            "name" => {
+                node.image = resources
+                    .images
+                    .iter()
+                    .position(|image| image.name == value)
+                    .ok_or((ErrorKind::UnknownImage, value))?;
+            }
+            "hole" => node.hole = number()?,
+            "start" => node.start = real()?,
+            "min" => node.min = Some(real()?),
+            "max" => node.max = Some(real()?),
+            "value" => node.value = real()?,
+            "stroke" => node.stroke = number()?,
+            "markers" if value.is_empty() => node.markers = true,
+            _ => return Err((ErrorKind::UnknownAttribute, token)),
+        }
+    }
+
+    let bad_content = (ErrorKind::BadContent, content);
+    match kind {
+        Kind::Text | Kind::Pie | Kind::Bar | Kind::Gauge
+            if resources.fonts.get(node.font).is_none() =>
+        {
+            return Err((ErrorKind::UnknownFont, name));
+        }
+        Kind::Image if resources.images.get(node.image).is_none() => {
+            return Err((ErrorKind::UnknownImage, name));
+        }
+        Kind::Pie | Kind::Bar => {
+            parse_data(content, &mut [Datum::new("", 0.0); MAX_VALUES]).ok_or(bad_content)?;
+        }
+        Kind::Line => {
+            parse_values(content, &mut [0.0; MAX_VALUES]).ok_or(bad_content)?;
+        }
+        _ => {}
+    }
+    Ok(node)
+}
+
+/// Parses `LABEL VALUE` pairs separated by commas into `data`, returning
+/// their number.
+fn parse_data<'a>(content: &'a str, data: &mut [Datum<'a>; MAX_VALUES]) -> Option<usize> {
+    let mut count = 0;
+    for item in content
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let (label, value) = item.rsplit_once(' ').unwrap_or(("", item));
+        *data.get_mut(count)? = Datum::new(label.trim(), value.parse().ok()?);
+        count += 1;
+    }
+    Some(count)
+}
+
+/// Parses space separated values into `values`, returning their number.
+fn parse_values(content: &str, values: &mut [f32; MAX_VALUES]) -> Option<usize> {
+    let mut count = 0;
+    for value in content.split_whitespace() {
+        *values.get_mut(count)? = value.parse().ok()?;
+        count += 1;
+    }
+    Some(count)
+}
+
+/// `bounds` shrunk by `padding` on every side.
+fn inset(bounds: Rectangle, padding: u32) -> Rectangle {
+    Rectangle::new(
+        bounds.top_left + Point::new(padding as i32, padding as i32),
+        bounds
+            .size
+            .saturating_sub(Size::new(2 * padding, 2 * padding)),
+    )
+}
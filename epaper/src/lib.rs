@@ -1,2 +1,17 @@
 #![no_std]
-pub mod xkcd_font;
+
+pub mod app;
+pub mod chart;
+pub mod dashboard_screen;
+pub mod gag_screen;
+pub mod layout;
+pub mod pie_screen;
+pub mod refresh;
+pub mod screen;
+pub mod sleep;
+pub mod text;
+
+/// The fonts listed in `fonts.txt`, converted by the build script.
+pub mod fonts {
+    include!(concat!(env!("OUT_DIR"), "/fonts.rs"));
+}
@@ -0,0 +1,45 @@
+use embedded_graphics::prelude::*;
+use epd_waveshare::color::Color;
+use u8g2_fonts::U8g2TextStyle;
+
+use crate::chart::{Datum, PieChart};
+use crate::fonts::Xkcd;
+use crate::screen::Screen;
+
+/// A pie chart of the time taken finding letters versus rendering it.
+#[derive(Debug, Default)]
+pub struct PieScreen {
+    drawn: bool,
+}
+
+impl PieScreen {
+    pub const fn new() -> Self {
+        Self { drawn: false }
+    }
+}
+
+impl Screen for PieScreen {
+    fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        target.clear(Color::White)?;
+
+        let slices = [
+            Datum::new("TIME TAKEN\nFINDING\nLETTERS", 7.0),
+            Datum::new("TIME TAKEN\nRENDERING\nPIE CHART", 1.0),
+        ];
+        let style = U8g2TextStyle::new(Xkcd, Color::Black);
+        // Centre the big slice on the left, so the small one is on the right.
+        PieChart::new(target.bounding_box().offset(-8), &slices, style)
+            .with_start(180.0 - 7.0 / 8.0 * 180.0)
+            .draw(target)?;
+
+        self.drawn = true;
+        Ok(())
+    }
+
+    fn changed(&self) -> bool {
+        !self.drawn
+    }
+}
@@ -0,0 +1,139 @@
+//! Decides how each new frame is put on the panel.
+//!
+//! A full refresh flashes the whole panel but leaves no ghosting, a quick
+//! refresh with the partial LUT only drives the pixels that changed. The
+//! manager keeps the last shown frame, diffs the next one against it and
+//! picks a quick refresh for small changes, with a full refresh every few
+//! quick ones to clear the ghosting they leave behind.
+//!
+//! The diff is of whole frames: what changed since the last frame, not
+//! what the screens drew. Both kinds of refresh send the whole frame.
+
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::primitives::Rectangle;
+
+/// How a frame should be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refresh {
+    /// Nothing changed since the last frame.
+    None,
+    /// Quick refresh of the whole frame. The rectangle bounds the pixels
+    /// changed since the last frame, it is not a window to send.
+    Quick(Rectangle),
+    /// Full refresh.
+    Full,
+}
+
+/// Tracks the frames shown on a panel `width` pixels wide with an `N` byte
+/// buffer, one bit per pixel and rows starting on a byte.
+///
+/// Rectangles are in panel coordinates, before any display rotation.
+#[derive(Debug, Clone)]
+pub struct RefreshManager<const N: usize> {
+    previous: [u8; N],
+    width: u32,
+    full_every: u32,
+    quick_area: u32,
+    quick: u32,
+    shown: bool,
+}
+
+impl<const N: usize> RefreshManager<N> {
+    /// A full refresh every 10 quick ones, quick refreshes for changes up to
+    /// a quarter of the panel.
+    pub const fn new(width: u32) -> Self {
+        let height = (N / width.div_ceil(8) as usize) as u32;
+        Self {
+            previous: [0; N],
+            width,
+            full_every: 10,
+            quick_area: width * height / 4,
+            quick: 0,
+            shown: false,
+        }
+    }
+
+    /// Forces a full refresh after `count` quick refreshes in a row.
+    pub const fn with_full_every(mut self, count: u32) -> Self {
+        self.full_every = count;
+        self
+    }
+
+    /// Largest changed area, in pixels, shown with a quick refresh.
+    pub const fn with_quick_area(mut self, area: u32) -> Self {
+        self.quick_area = area;
+        self
+    }
+
+    /// Carries on from `previous`, shown after `quick` quick refreshes in a
+    /// row, such as after the panel kept it through a deep sleep.
+    pub fn with_shown(mut self, previous: &[u8; N], quick: u32) -> Self {
+        self.previous = *previous;
+        self.quick = quick;
+        self.shown = true;
+        self
+    }
+
+    /// The frame shown last, what the panel's old frame RAM should hold for
+    /// a quick refresh.
+    pub fn previous(&self) -> &[u8] {
+        &self.previous
+    }
+
+    /// Quick refreshes since the last full one.
+    pub fn quick_count(&self) -> u32 {
+        self.quick
+    }
+
+    /// The smallest rectangle holding every pixel of `buffer` that changed
+    /// since the frame shown last.
+    pub fn changed(&self, buffer: &[u8]) -> Option<Rectangle> {
+        let row_bytes = self.width.div_ceil(8) as usize;
+        let mut min = Point::new(i32::MAX, i32::MAX);
+        let mut max = Point::new(i32::MIN, i32::MIN);
+        for (i, (new, old)) in buffer.iter().zip(&self.previous).enumerate() {
+            let diff = new ^ old;
+            if diff == 0 {
+                continue;
+            }
+            let x = (i % row_bytes * 8) as i32;
+            let y = (i / row_bytes) as i32;
+            min.x = min.x.min(x + diff.leading_zeros() as i32);
+            max.x = max.x.max(x + 7 - diff.trailing_zeros() as i32);
+            min.y = min.y.min(y);
+            max.y = max.y.max(y);
+        }
+        if min.x > max.x {
+            return None;
+        }
+        Some(Rectangle::with_corners(min, max))
+    }
+
+    /// How to show `buffer`. Call `shown` once it is on the panel, until then
+    /// `previous` still holds the old frame.
+    pub fn plan(&self, buffer: &[u8]) -> Refresh {
+        if !self.shown {
+            return Refresh::Full;
+        }
+        let Some(changed) = self.changed(buffer) else {
+            return Refresh::None;
+        };
+        let Size { width, height } = changed.size;
+        if self.quick >= self.full_every || width * height > self.quick_area {
+            Refresh::Full
+        } else {
+            Refresh::Quick(changed)
+        }
+    }
+
+    /// Records `buffer` as shown with `refresh`.
+    pub fn shown(&mut self, buffer: &[u8], refresh: Refresh) {
+        match refresh {
+            Refresh::None => return,
+            Refresh::Quick(_) => self.quick += 1,
+            Refresh::Full => self.quick = 0,
+        }
+        self.previous.copy_from_slice(buffer);
+        self.shown = true;
+    }
+}
@@ -0,0 +1,14 @@
+use embedded_graphics::draw_target::DrawTarget;
+use epd_waveshare::color::Color;
+
+/// A full-screen view of the display.
+pub trait Screen {
+    /// Draws the whole screen on `target`, which it clears first.
+    fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>;
+
+    /// Whether the screen looks different from what its last `draw` drew,
+    /// true before the first `draw`.
+    fn changed(&self) -> bool;
+}
@@ -0,0 +1,86 @@
+//! State kept in RTC memory while the ESP32 is in deep sleep.
+//!
+//! E-paper keeps its image without power, so the firmware draws a screen,
+//! puts the panel and then itself into deep sleep, and carries on from this
+//! state when a timer or a button wakes it up again.
+//!
+//! The memory is not initialised again at boot and a reset can leave it
+//! half written, so it only holds integers and bytes, which are valid for
+//! any contents, and a magic number written last.
+
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use crate::app::App;
+use crate::refresh::RefreshManager;
+
+/// Marks memory holding a saved state.
+const MAGIC: u32 = 0x4550_4150;
+
+/// What the firmware remembers between two updates, for a panel with an `N`
+/// byte buffer.
+#[derive(Debug)]
+pub struct Retained<const N: usize> {
+    magic: u32,
+    /// Wakeups since the state was last started over.
+    pub wakes: u32,
+    /// Quick refreshes since the last full one.
+    quick: u32,
+    /// `App::screen` of the screen shown.
+    screen: u8,
+    /// The frame on the panel.
+    previous: [u8; N],
+}
+
+// SAFETY: every field is an integer or an array of them, valid for any bit
+// pattern.
+#[cfg(target_arch = "xtensa")]
+unsafe impl<const N: usize> esp_hal::Persistable for Retained<N> {}
+
+impl<const N: usize> Retained<N> {
+    /// Nothing saved, as the memory is after power on.
+    pub const fn new() -> Self {
+        Self {
+            magic: 0,
+            wakes: 0,
+            quick: 0,
+            screen: 0,
+            previous: [0; N],
+        }
+    }
+
+    /// The app and refresh manager saved before the deep sleep, when `woke`
+    /// from it. Otherwise, such as after power on or a reset, or when
+    /// nothing was saved, new ones for a panel `width` pixels wide.
+    pub fn restore(&mut self, woke: bool, width: u32) -> (App, RefreshManager<N>) {
+        let refresh = RefreshManager::new(width);
+        if woke && self.magic == MAGIC {
+            self.wakes = self.wakes.wrapping_add(1);
+            return (
+                App::with_screen(self.screen),
+                refresh.with_shown(&self.previous, self.quick),
+            );
+        }
+        self.magic = 0;
+        self.wakes = 0;
+        (App::new(), refresh)
+    }
+
+    /// Saves `app` and `refresh` for the next wakeup. Call it once `refresh`
+    /// has shown a frame.
+    pub fn save(&mut self, app: &App, refresh: &RefreshManager<N>) {
+        // A reset before the magic is written back starts over.
+        self.magic = 0;
+        compiler_fence(Ordering::SeqCst);
+        self.quick = refresh.quick_count();
+        self.screen = app.screen();
+        self.previous.copy_from_slice(refresh.previous());
+        compiler_fence(Ordering::SeqCst);
+        self.magic = MAGIC;
+    }
+}
+
+impl<const N: usize> Default for Retained<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,374 @@
+//! Text laid out in a rectangle: wrapped at spaces, aligned, and cut off with
+//! an ellipsis when it does not fit.
+//!
+//! The text is a list of runs, each in its own u8g2 font, which flow into
+//! each other on the same lines. Glyphs missing from a run's font are
+//! skipped.
+
+use embedded_graphics::{prelude::*, primitives::Rectangle, text::Alignment};
+use epd_waveshare::color::Color;
+use u8g2_fonts::{
+    types::{FontColor, VerticalPosition},
+    Error, FontRenderer,
+};
+
+const ELLIPSIS: &str = "...";
+
+/// Text in one font.
+#[derive(Debug, Clone, Copy)]
+pub struct Run<'a> {
+    pub text: &'a str,
+    pub font: &'a FontRenderer,
+}
+
+impl<'a> Run<'a> {
+    pub const fn new(text: &'a str, font: &'a FontRenderer) -> Self {
+        Self { text, font }
+    }
+}
+
+/// Where the lines go when they are shorter than the box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerticalAlignment {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Runs of text wrapped into `bounds`.
+#[derive(Debug, Clone)]
+pub struct TextBox<'a> {
+    bounds: Rectangle,
+    runs: &'a [Run<'a>],
+    color: Color,
+    alignment: Alignment,
+    vertical: VerticalAlignment,
+    spacing: i32,
+    ellipsis: bool,
+}
+
+/// Position of a character, as run index and byte offset in its text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cursor {
+    run: usize,
+    byte: usize,
+}
+
+/// Extent of the fonts used on a line.
+#[derive(Debug, Clone, Copy, Default)]
+struct Metrics {
+    ascent: i32,
+    descent: i32,
+    line_height: i32,
+}
+
+impl Metrics {
+    fn add(&mut self, font: &FontRenderer) {
+        let bounds = font.get_font_bounding_box(VerticalPosition::Baseline);
+        let ascent = -bounds.top_left.y;
+        self.ascent = self.ascent.max(ascent);
+        self.descent = self.descent.max(bounds.size.height as i32 - ascent);
+        self.line_height = self.line_height.max(font.get_default_line_height() as i32);
+    }
+
+    fn height(&self) -> i32 {
+        self.line_height.max(self.ascent + self.descent)
+    }
+}
+
+/// A line of text from `start` to before `end`, the next line starts at
+/// `next`.
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    start: Cursor,
+    end: Cursor,
+    next: Cursor,
+    width: u32,
+    metrics: Metrics,
+}
+
+impl<'a> TextBox<'a> {
+    /// Black, left and top aligned text, with an ellipsis when cut off.
+    pub const fn new(bounds: Rectangle, runs: &'a [Run<'a>]) -> Self {
+        Self {
+            bounds,
+            runs,
+            color: Color::Black,
+            alignment: Alignment::Left,
+            vertical: VerticalAlignment::Top,
+            spacing: 0,
+            ellipsis: true,
+        }
+    }
+
+    pub const fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub const fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub const fn with_vertical_alignment(mut self, alignment: VerticalAlignment) -> Self {
+        self.vertical = alignment;
+        self
+    }
+
+    /// Adds `pixels`, which may be negative, between lines.
+    pub const fn with_line_spacing(mut self, pixels: i32) -> Self {
+        self.spacing = pixels;
+        self
+    }
+
+    /// Without an ellipsis, lines that do not fit are left out.
+    pub const fn with_ellipsis(mut self, ellipsis: bool) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    /// Size of the wrapped text, as if the box was tall enough for all of it.
+    pub fn size(&self) -> Size {
+        let (mut width, mut height) = (0, 0);
+        let mut count = 0;
+        let mut start = self.start();
+        while let Some(line) = self.line(start) {
+            width = width.max(line.width);
+            height += line.metrics.height();
+            count += 1;
+            start = line.next;
+        }
+        let spacing = if count > 1 {
+            self.spacing * (count - 1)
+        } else {
+            0
+        };
+        Size::new(width, (height + spacing).max(0) as u32)
+    }
+
+    /// Whether all the text fits in the box.
+    pub fn fits(&self) -> bool {
+        self.visible().1.is_none()
+    }
+
+    /// Height of the lines that fit, and the last of them if it is followed
+    /// by lines that do not.
+    fn visible(&self) -> (i32, Option<Line>) {
+        let available = self.bounds.size.height as i32;
+        let mut height = 0;
+        let mut previous: Option<Line> = None;
+        let mut start = self.start();
+        while let Some(line) = self.line(start) {
+            let spacing = if previous.is_some() { self.spacing } else { 0 };
+            if height + spacing + line.metrics.height() > available {
+                return (height, previous.or(Some(line)));
+            }
+            height += spacing + line.metrics.height();
+            previous = Some(line);
+            start = line.next;
+        }
+        (height, None)
+    }
+
+    fn start(&self) -> Cursor {
+        Cursor { run: 0, byte: 0 }
+    }
+
+    fn end(&self) -> Cursor {
+        Cursor {
+            run: self.runs.len(),
+            byte: 0,
+        }
+    }
+
+    /// Characters from `from` on, with their position and font.
+    fn chars(&self, from: Cursor) -> impl Iterator<Item = (Cursor, char, &'a FontRenderer)> {
+        let runs: &'a [Run<'a>] = self.runs;
+        runs.iter()
+            .enumerate()
+            .skip(from.run)
+            .flat_map(move |(run, Run { text, font })| {
+                let skip = if run == from.run { from.byte } else { 0 };
+                text[skip..].char_indices().map(move |(byte, ch)| {
+                    (
+                        Cursor {
+                            run,
+                            byte: skip + byte,
+                        },
+                        ch,
+                        *font,
+                    )
+                })
+            })
+    }
+
+    /// The line starting at `start`, broken at the last space that fits or
+    /// within a word too long for a line of its own.
+    fn line(&self, start: Cursor) -> Option<Line> {
+        let mut chars = self.chars(start).peekable();
+        chars.peek()?;
+
+        let mut line = Line {
+            start,
+            end: self.end(),
+            next: self.end(),
+            width: 0,
+            metrics: Metrics::default(),
+        };
+        let mut space: Option<Line> = None;
+        while let Some((at, ch, font)) = chars.next() {
+            let after = chars.peek().map_or(self.end(), |&(next, _, _)| next);
+            if ch == '\n' {
+                line.metrics.add(font);
+                line.end = at;
+                line.next = after;
+                return Some(line);
+            }
+            let advance = advance(font, ch);
+            if ch == ' ' {
+                space = Some(Line {
+                    end: at,
+                    next: after,
+                    ..line
+                });
+            } else if line.width + advance > self.bounds.size.width && at != start {
+                return Some(space.unwrap_or(Line {
+                    end: at,
+                    next: at,
+                    ..line
+                }));
+            }
+            line.metrics.add(font);
+            line.width += advance;
+        }
+        Some(line)
+    }
+
+    /// `line` shortened to leave room for an ellipsis, and the font to draw
+    /// the ellipsis in.
+    fn truncate(&self, line: Line) -> (Line, &'a FontRenderer) {
+        let mut font = self.runs[line.start.run].font;
+        let mut width = 0;
+        let mut shown = line.start;
+        let mut shown_width = 0;
+        for (at, ch, char_font) in self.chars(line.start) {
+            let advance = advance(char_font, ch);
+            let ellipsis = text_width(char_font, ELLIPSIS);
+            if ch == '\n' || width + advance + ellipsis > self.bounds.size.width {
+                break;
+            }
+            width += advance;
+            if ch != ' ' {
+                let end = Cursor {
+                    run: at.run,
+                    byte: at.byte + ch.len_utf8(),
+                };
+                (shown, shown_width, font) = (end, width, char_font);
+            }
+        }
+        let line = Line {
+            end: shown,
+            width: shown_width + text_width(font, ELLIPSIS),
+            ..line
+        };
+        (line, font)
+    }
+
+    /// Draws the characters of `line` on the baseline at `y`, returning where
+    /// the line ends.
+    fn draw_line<D>(&self, target: &mut D, line: &Line, y: i32) -> Result<i32, D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let mut x = self.line_left(line.width);
+        for (at, ch, font) in self.chars(line.start) {
+            if at.run > line.end.run || (at.run == line.end.run && at.byte >= line.end.byte) {
+                break;
+            }
+            x += draw_char(target, font, ch, Point::new(x, y), self.color)?;
+        }
+        Ok(x)
+    }
+
+    fn line_left(&self, width: u32) -> i32 {
+        let free = self.bounds.size.width.saturating_sub(width) as i32;
+        self.bounds.top_left.x
+            + match self.alignment {
+                Alignment::Left => 0,
+                Alignment::Center => free / 2,
+                Alignment::Right => free,
+            }
+    }
+}
+
+impl Drawable for TextBox<'_> {
+    type Color = Color;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let (height, last) = self.visible();
+        let last = if self.ellipsis { last } else { None };
+        let free = self.bounds.size.height as i32 - height;
+        let mut top = self.bounds.top_left.y
+            + match self.vertical {
+                VerticalAlignment::Top => 0,
+                VerticalAlignment::Middle => free / 2,
+                VerticalAlignment::Bottom => free,
+            };
+
+        let mut start = self.start();
+        while let Some(line) = self.line(start) {
+            if top + line.metrics.height() > self.bounds.top_left.y + self.bounds.size.height as i32
+            {
+                break;
+            }
+            let baseline = top + line.metrics.ascent;
+            if last.is_some_and(|last| last.start == line.start) {
+                let (line, font) = self.truncate(line);
+                let mut x = self.draw_line(target, &line, baseline)?;
+                for ch in ELLIPSIS.chars() {
+                    x += draw_char(target, font, ch, Point::new(x, baseline), self.color)?;
+                }
+                break;
+            }
+            self.draw_line(target, &line, baseline)?;
+            top += line.metrics.height() + self.spacing;
+            start = line.next;
+        }
+        Ok(())
+    }
+}
+
+fn advance(font: &FontRenderer, ch: char) -> u32 {
+    font.get_rendered_dimensions(ch, Point::zero(), VerticalPosition::Baseline)
+        .map_or(0, |dimensions| dimensions.advance.x as u32)
+}
+
+fn text_width(font: &FontRenderer, text: &str) -> u32 {
+    text.chars().map(|ch| advance(font, ch)).sum()
+}
+
+/// Draws `ch` with its baseline at `position`, returning its advance. A
+/// glyph missing from the font is skipped.
+fn draw_char<D>(
+    target: &mut D,
+    font: &FontRenderer,
+    ch: char,
+    position: Point,
+    color: Color,
+) -> Result<i32, D::Error>
+where
+    D: DrawTarget<Color = Color>,
+{
+    let color = FontColor::Transparent(color);
+    match font.render(ch, position, VerticalPosition::Baseline, color, target) {
+        Ok(dimensions) => Ok(dimensions.advance.x),
+        Err(Error::DisplayError(e)) => Err(e),
+        Err(_) => Ok(0),
+    }
+}
@@ -1,36 +0,0 @@
-
-use u8g2_fonts::Font;
-
-/// A struct representing the xkcd font.
-pub struct Xkcd;
-
-/// Implement the `Font` trait for the `Xkcd` struct.
-/// This tells the `u8g2_fonts` crate where to find the font data.
-impl Font for Xkcd {
-    const DATA: &'static [u8] = &[
-    0x1B, 0x00, 0x03, 0x02, 0x04, 0x04, 0x02, 0x02, 0x05, 0x09, 0x0B, 0x00, 0xFF, 0x09, 0xFD, 0x0A,
-    0xFF, 0x00, 0x05, 0x00, 0x00, 0x01, 0x57, 0x20, 0x05, 0x00, 0x4A, 0x01, 0x41, 0x0D, 0x96, 0x7A,
-    0xB5, 0x25, 0x51, 0xA4, 0x64, 0xC3, 0x4D, 0x34, 0x36, 0x42, 0x0E, 0x95, 0x7B, 0xB3, 0x64, 0xD2,
-    0xB0, 0x24, 0x4A, 0xB4, 0x69, 0xC3, 0x00, 0x43, 0x0E, 0xA6, 0x8B, 0x17, 0x0E, 0x89, 0x26, 0xA6,
-    0x8D, 0x93, 0x32, 0x24, 0x00, 0x44, 0x10, 0x97, 0x8B, 0x31, 0x64, 0xD1, 0x12, 0x26, 0xE1, 0x31,
-    0xC9, 0x94, 0x64, 0x1A, 0x01, 0x45, 0x0E, 0x97, 0x8A, 0x35, 0x28, 0x6A, 0x3C, 0x64, 0x71, 0xCB,
-    0x30, 0x68, 0x00, 0x46, 0x0C, 0x87, 0x8E, 0x33, 0x2C, 0x71, 0x79, 0xC8, 0x52, 0x2B, 0x00, 0x47,
-    0x10, 0x97, 0x8B, 0x33, 0x64, 0x91, 0xA2, 0xC6, 0xD1, 0x12, 0x8E, 0x89, 0xA4, 0x4D, 0x00, 0x48,
-    0x0B, 0x85, 0x7B, 0x91, 0xD9, 0x86, 0x41, 0xD2, 0x6C, 0x01, 0x49, 0x06, 0x91, 0x3B, 0xF1, 0x00,
-    0x4A, 0x0C, 0x98, 0x9B, 0x37, 0x28, 0x43, 0xDE, 0x59, 0x4B, 0x54, 0x15, 0x4B, 0x0F, 0x97, 0x8A,
-    0x21, 0x29, 0xA6, 0x21, 0x54, 0xC7, 0x44, 0x8B, 0xA4, 0x4C, 0x0E, 0x4C, 0x0A, 0x86, 0x8F, 0x91,
-    0xF6, 0x69, 0x18, 0x22, 0x00, 0x4D, 0x0C, 0xA7, 0x97, 0xBB, 0x25, 0x8B, 0x32, 0x48, 0xAA, 0xCF,
-    0x00, 0x4E, 0x0C, 0xA6, 0x8B, 0x2B, 0x6E, 0x87, 0x48, 0x31, 0x0D, 0xD9, 0x15, 0x4F, 0x0E, 0x97,
-    0x8A, 0xB5, 0x29, 0xB6, 0xD5, 0x78, 0x64, 0x51, 0x24, 0x6D, 0x00, 0x50, 0x0D, 0x96, 0x8F, 0x31,
-    0x28, 0xD9, 0xA8, 0x0D, 0x43, 0x92, 0xB6, 0x02, 0x51, 0x12, 0x98, 0xAB, 0x35, 0x2A, 0x92, 0xA6,
-    0xA4, 0x49, 0x9A, 0x44, 0x95, 0x68, 0x48, 0xA4, 0x43, 0x14, 0x52, 0x0E, 0x96, 0x8B, 0x71, 0x08,
-    0xB5, 0x49, 0xD9, 0xB6, 0xA8, 0x96, 0x64, 0x12, 0x53, 0x0F, 0x97, 0x8A, 0x35, 0x44, 0x52, 0x75,
-    0x98, 0xCC, 0x49, 0x98, 0x48, 0xD9, 0x04, 0x54, 0x08, 0x96, 0x8B, 0xF1, 0xDA, 0x6F, 0x00, 0x55,
-    0x0A, 0x96, 0x8B, 0x39, 0xFA, 0xB6, 0x45, 0x13, 0x00, 0x56, 0x0F, 0x96, 0x7A, 0x9B, 0x4E, 0x4A,
-    0xA4, 0x44, 0x91, 0x92, 0x25, 0xD9, 0x18, 0x01, 0x57, 0x0C, 0xA8, 0xAB, 0x3D, 0xFB, 0x2D, 0x96,
-    0x45, 0x53, 0x01, 0x58, 0x0F, 0x96, 0x8B, 0x91, 0x25, 0xA6, 0x24, 0x13, 0xC5, 0x24, 0x52, 0x94,
-    0x4C, 0x0D, 0x59, 0x0C, 0x97, 0x8A, 0x91, 0x8E, 0x49, 0x56, 0xD1, 0xD6, 0xB8, 0x0D, 0x5A, 0x0D,
-    0x88, 0x8E, 0x33, 0x0C, 0x49, 0x18, 0xA7, 0x72, 0xF3, 0x30, 0x08, 0x00, 0x00, 0x00, 0x04, 0xFF,
-    0xFF, 0x00,
-];
-}
@@ -0,0 +1,196 @@
+//! Host benchmarks for the effects in `led-effects`.
+//!
+//! Every built-in effect runs `before_render` plus a full-strip render at
+//! 16, 144 and 1000 LEDs. The report shows the median time per frame and the
+//! heap allocations made while creating the effect and while rendering.
+//!
+//! Results can be saved and compared on another commit:
+//!
+//! ```text
+//! cargo run --release -- --save main.csv
+//! git checkout my-branch
+//! cargo run --release -- --baseline main.csv
+//! cargo run --release --features fixed-point -- --baseline main.csv
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use std::{env, fs, process};
+
+use led_effects::effect::LedEffect;
+use led_effects::registry::{self, Effect};
+use smart_leds::RGB8;
+
+/// Counts every allocation made by the process.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+/// Seconds per frame passed to `before_render`, as at 20 frames per second.
+const DELTA: f32 = 0.05;
+const WARMUP_FRAMES: u32 = 100;
+const SAMPLES: usize = 21;
+/// Each sample renders enough frames to take about this long.
+const SAMPLE_TIME: Duration = Duration::from_millis(10);
+
+struct Measurement {
+    effect: &'static str,
+    leds: usize,
+    ns_per_frame: f64,
+    create_allocations: usize,
+    frame_allocations: f64,
+}
+
+fn main() {
+    let mut save = None;
+    let mut baseline = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save" => save = Some(args.next().unwrap_or_else(|| usage())),
+            "--baseline" => baseline = Some(args.next().unwrap_or_else(|| usage())),
+            _ => usage(),
+        }
+    }
+
+    let baseline = baseline.map(|path| match fs::read_to_string(&path) {
+        Ok(csv) => parse_csv(&csv),
+        Err(e) => {
+            eprintln!("cannot read {path}: {e}");
+            process::exit(1);
+        }
+    });
+
+    let mut results = Vec::new();
+    bench::<16>(&mut results);
+    bench::<144>(&mut results);
+    bench::<1000>(&mut results);
+
+    let math = if cfg!(feature = "fixed-point") {
+        "fixed-point"
+    } else {
+        "float"
+    };
+    println!("{math} math, median of {SAMPLES} samples");
+    println!(
+        "{:<12} {:>5} {:>12} {:>14} {:>13} {:>12}",
+        "effect", "leds", "ns/frame", "create allocs", "allocs/frame", "vs baseline"
+    );
+    for m in &results {
+        let change = baseline
+            .as_ref()
+            .and_then(|rows| rows.iter().find(|row| row.0 == m.effect && row.1 == m.leds))
+            .map(|row| format!("{:+.1}%", (m.ns_per_frame / row.2 - 1.0) * 100.0))
+            .unwrap_or_default();
+        println!(
+            "{:<12} {:>5} {:>12.0} {:>14} {:>13.2} {:>12}",
+            m.effect, m.leds, m.ns_per_frame, m.create_allocations, m.frame_allocations, change
+        );
+    }
+
+    if let Some(path) = save {
+        if let Err(e) = fs::write(&path, to_csv(&results)) {
+            eprintln!("cannot write {path}: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("usage: led-bench [--save FILE] [--baseline FILE]");
+    process::exit(2);
+}
+
+fn bench<const N: usize>(results: &mut Vec<Measurement>) {
+    let mut leds = [RGB8::default(); N];
+    for factory in registry::effects::<N>() {
+        let before = allocations();
+        let mut effect = factory.create(&[]);
+        let create_allocations = allocations() - before;
+
+        for _ in 0..WARMUP_FRAMES {
+            frame(&mut effect, &mut leds);
+        }
+        let start = Instant::now();
+        for _ in 0..WARMUP_FRAMES {
+            frame(&mut effect, &mut leds);
+        }
+        let per_frame = start.elapsed() / WARMUP_FRAMES;
+        let frames = (SAMPLE_TIME.as_nanos() / per_frame.as_nanos().max(1)).max(1) as u32;
+
+        let before = allocations();
+        let mut samples = [0.0; SAMPLES];
+        for sample in samples.iter_mut() {
+            let start = Instant::now();
+            for _ in 0..frames {
+                frame(&mut effect, &mut leds);
+            }
+            *sample = start.elapsed().as_nanos() as f64 / frames as f64;
+        }
+        let frame_allocations = (allocations() - before) as f64 / (frames as usize * SAMPLES) as f64;
+
+        samples.sort_by(f64::total_cmp);
+        results.push(Measurement {
+            effect: factory.name,
+            leds: N,
+            ns_per_frame: samples[SAMPLES / 2],
+            create_allocations,
+            frame_allocations,
+        });
+    }
+}
+
+fn frame<const N: usize>(effect: &mut Effect<N>, leds: &mut [RGB8; N]) {
+    effect.before_render(black_box(DELTA));
+    for (i, led) in leds.iter_mut().enumerate() {
+        *led = effect.render(i, N);
+    }
+    black_box(leds);
+}
+
+fn to_csv(results: &[Measurement]) -> String {
+    let mut csv = String::from("effect,leds,ns_per_frame\n");
+    for m in results {
+        csv += &format!("{},{},{:.1}\n", m.effect, m.leds, m.ns_per_frame);
+    }
+    csv
+}
+
+/// Reads the rows written by `to_csv`, skipping lines it cannot parse.
+fn parse_csv(csv: &str) -> Vec<(String, usize, f64)> {
+    csv.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let effect = fields.next()?.to_string();
+            let leds = fields.next()?.parse().ok()?;
+            let ns_per_frame = fields.next()?.parse().ok()?;
+            Some((effect, leds, ns_per_frame))
+        })
+        .collect()
+}
@@ -1,7 +1,11 @@
 fn main() {
+    // The link arguments are for the firmware, host test binaries link without them.
+    if std::env::var("CARGO_CFG_TARGET_ARCH").as_deref() != Ok("riscv32") {
+        return;
+    }
     linker_be_nice();
     // make sure linkall.x is the last linker script (otherwise might cause problems with flip-link)
-    println!("cargo:rustc-link-arg=-Tlinkall.x");
+    println!("cargo:rustc-link-arg-bins=-Tlinkall.x");
 }
 
 fn linker_be_nice() {
@@ -46,7 +50,7 @@ fn linker_be_nice() {
     }
 
     println!(
-        "cargo:rustc-link-arg=--error-handling-script={}",
+        "cargo:rustc-link-arg-bins=--error-handling-script={}",
         std::env::current_exe().unwrap().display()
     );
 }
@@ -0,0 +1,176 @@
+//! The MQTT light of the firmware on a Linux host, against a local broker
+//! such as mosquitto. It runs the same discovery, command and state flow
+//! with rust-mqtt, but on a controller without LEDs.
+//!
+//! ```text
+//! mosquitto -v
+//! cargo run --target x86_64-unknown-linux-gnu --no-default-features --features alloc \
+//!     --example mqtt_light -- 127.0.0.1:1883
+//! mosquitto_sub -v -t 'homeassistant/light/#' -t 'led-effects/#'
+//! mosquitto_pub -t 'led-effects/led-effects_000000000001/set' -m '{"state":"ON","effect":"Rocket"}'
+//! ```
+//!
+//! Home Assistant pointed at the same broker shows the light too.
+
+use std::io::{self, Read as _, Write as _};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+use std::{env, thread};
+
+use embassy_futures::block_on;
+use embedded_io_async::{ErrorKind, ErrorType, Read, ReadReady, Write};
+use led_effects::controller::EffectController;
+use led_effects::mqtt::{self, Topics, PAYLOAD_OFFLINE, PAYLOAD_ONLINE};
+use led_effects::registry;
+use rust_mqtt::{
+    client::{
+        client::MqttClient,
+        client_config::{ClientConfig, MqttVersion},
+    },
+    packet::v5::{publish_packet::QualityOfService, reason_codes::ReasonCode},
+    utils::rng_generator::CountingRng,
+};
+
+const NUM_LEDS: usize = 16;
+
+/// A made up MAC address, the firmware uses the one of the chip.
+const MAC: [u8; 6] = [0, 0, 0, 0, 0, 1];
+
+/// A blocking `TcpStream` for rust-mqtt, which only needs the async traits.
+struct Connection(TcpStream);
+
+/// rust-mqtt only tells network errors apart from the others.
+fn kind(_: io::Error) -> ErrorKind {
+    ErrorKind::Other
+}
+
+impl ErrorType for Connection {
+    type Error = ErrorKind;
+}
+
+impl Read for Connection {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.0.read(buf).map_err(kind)
+    }
+}
+
+impl Write for Connection {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.0.write(buf).map_err(kind)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.0.flush().map_err(kind)
+    }
+}
+
+impl ReadReady for Connection {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        self.0.set_nonblocking(true).map_err(kind)?;
+        let ready = match self.0.peek(&mut [0]) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(kind(e)),
+        };
+        self.0.set_nonblocking(false).map_err(kind)?;
+        ready
+    }
+}
+
+fn main() {
+    let broker = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:1883".into());
+    let mut controller = EffectController::<NUM_LEDS>::new();
+    for factory in registry::effects() {
+        controller.register(factory).unwrap();
+    }
+    let node_id = mqtt::node_id(MAC);
+    let topics = Topics::new(&node_id);
+
+    let stream = TcpStream::connect(&broker).unwrap_or_else(|e| {
+        eprintln!("cannot connect to {broker}: {e}");
+        std::process::exit(1);
+    });
+    let mut write_buffer = [0u8; 1024];
+    let mut recv_buffer = [0u8; 1024];
+    let mut config = ClientConfig::new(MqttVersion::MQTTv5, CountingRng(20000));
+    config.add_client_id(&node_id);
+    config.add_will(&topics.availability, PAYLOAD_OFFLINE.as_bytes(), true);
+    config.max_packet_size = 1024;
+    config.keep_alive = 60;
+    let mut client = MqttClient::<_, 5, _>::new(
+        Connection(stream),
+        &mut write_buffer,
+        1024,
+        &mut recv_buffer,
+        1024,
+        config,
+    );
+
+    println!("{} on {broker}", topics.command);
+    if let Err(e) = block_on(session(&mut client, &mut controller, &node_id, &topics)) {
+        eprintln!("MQTT session ended: {e:?}");
+        std::process::exit(1);
+    }
+}
+
+/// The firmware's `mqtt_session`, with the controller passed in.
+async fn session(
+    client: &mut MqttClient<'_, Connection, 5, CountingRng>,
+    controller: &mut EffectController<NUM_LEDS>,
+    node_id: &str,
+    topics: &Topics,
+) -> Result<(), ReasonCode> {
+    let mut payload = [0u8; 768];
+    let mut state = [0u8; 256];
+    let mut published = Vec::new();
+    let mut last_ping = Instant::now();
+
+    client.connect_to_broker().await?;
+    let len = mqtt::discovery_config(controller, node_id, "LED Strip", topics, &mut payload)
+        .map_err(|_| ReasonCode::ImplementationSpecificError)?;
+    client
+        .send_message(
+            &topics.config,
+            &payload[..len],
+            QualityOfService::QoS0,
+            true,
+        )
+        .await?;
+    client
+        .send_message(
+            &topics.availability,
+            PAYLOAD_ONLINE.as_bytes(),
+            QualityOfService::QoS0,
+            true,
+        )
+        .await?;
+    client.subscribe_to_topic(&topics.command).await?;
+
+    loop {
+        let len = mqtt::state(controller, &mut state)
+            .map_err(|_| ReasonCode::ImplementationSpecificError)?;
+        if state[..len] != published[..] {
+            client
+                .send_message(&topics.state, &state[..len], QualityOfService::QoS0, true)
+                .await?;
+            published = state[..len].to_vec();
+            println!("{}", String::from_utf8_lossy(&published));
+        }
+
+        match client.receive_message_if_ready().await? {
+            Some((_, message)) => {
+                if let Err(e) = mqtt::apply_command(controller, message) {
+                    println!("Rejected {:?}: {e:?}", String::from_utf8_lossy(message));
+                }
+            }
+            None => thread::sleep(Duration::from_millis(100)),
+        }
+
+        if last_ping.elapsed() > Duration::from_secs(30) {
+            client.send_ping().await?;
+            last_ping = Instant::now();
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use smart_leds::RGB8;
+
+const MAGIC: &[u8; 3] = b"Ada";
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    /// Number of magic bytes matched so far.
+    Magic(usize),
+    CountHigh,
+    CountLow,
+    Checksum,
+    Data,
+}
+
+/// Byte-at-a-time decoder for the Adalight serial protocol spoken by
+/// Prismatik, Hyperion and friends.
+///
+/// ```text
+/// 'A' 'd' 'a' | count hi | count lo | count hi ^ count lo ^ 0x55 | R G B ...
+/// ```
+///
+/// The header carries the number of LEDs minus one. Up to `N` LEDs are kept,
+/// colours for LEDs beyond that are read and dropped.
+pub struct AdalightDecoder<const N: usize> {
+    state: State,
+    count_high: u8,
+    count_low: u8,
+    expected: usize,
+    received: usize,
+    pixels: [RGB8; N],
+}
+
+impl<const N: usize> AdalightDecoder<N> {
+    pub fn new() -> Self {
+        Self {
+            state: State::Magic(0),
+            count_high: 0,
+            count_low: 0,
+            expected: 0,
+            received: 0,
+            pixels: [RGB8::default(); N],
+        }
+    }
+
+    /// Feeds one received byte into the decoder.
+    /// Returns true when the byte completed a frame, which can then be read
+    /// with `pixels()` until the next byte is fed.
+    pub fn feed(&mut self, byte: u8) -> bool {
+        match self.state {
+            State::Magic(matched) => {
+                self.state = if byte == MAGIC[matched] {
+                    if matched + 1 == MAGIC.len() {
+                        State::CountHigh
+                    } else {
+                        State::Magic(matched + 1)
+                    }
+                } else if byte == MAGIC[0] {
+                    State::Magic(1)
+                } else {
+                    State::Magic(0)
+                };
+            }
+            State::CountHigh => {
+                self.count_high = byte;
+                self.state = State::CountLow;
+            }
+            State::CountLow => {
+                self.count_low = byte;
+                self.state = State::Checksum;
+            }
+            State::Checksum => {
+                if byte == self.count_high ^ self.count_low ^ 0x55 {
+                    let count = ((self.count_high as usize) << 8 | self.count_low as usize) + 1;
+                    self.expected = count * 3;
+                    self.received = 0;
+                    self.pixels = [RGB8::default(); N];
+                    self.state = State::Data;
+                } else {
+                    self.state = State::Magic(0);
+                }
+            }
+            State::Data => {
+                let index = self.received / 3;
+                if let Some(pixel) = self.pixels.get_mut(index) {
+                    match self.received % 3 {
+                        0 => pixel.r = byte,
+                        1 => pixel.g = byte,
+                        _ => pixel.b = byte,
+                    }
+                }
+                self.received += 1;
+                if self.received == self.expected {
+                    self.state = State::Magic(0);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// LED colours of the last completed frame.
+    pub fn pixels(&self) -> &[RGB8] {
+        &self.pixels
+    }
+}
+
+impl<const N: usize> Default for AdalightDecoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
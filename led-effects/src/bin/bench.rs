@@ -0,0 +1,50 @@
+//! Measures the frame cost of every built-in effect on the device.
+//!
+//! Flash with `cargo run --release --bin bench`, and again with
+//! `--features fixed-point` to compare the two math paths.
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp_hal::{clock::CpuClock, main, time::Instant};
+use esp_println::println;
+use led_effects::{effect::LedEffect, registry};
+use smart_leds::RGB8;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+const NUM_LEDS: usize = 144;
+const FRAMES: u32 = 200;
+// Seconds per frame passed to `before_render`, as at 20 frames per second.
+const DELTA: f32 = 0.05;
+
+#[main]
+fn main() -> ! {
+    let config = esp_hal::Config::default().with_cpu_clock(CpuClock::max());
+    let _peripherals = esp_hal::init(config);
+
+    let math = if cfg!(feature = "fixed-point") {
+        "fixed-point"
+    } else {
+        "float"
+    };
+    println!("{} LEDs, {} frames, {} math\r", NUM_LEDS, FRAMES, math);
+
+    let mut leds = [RGB8::default(); NUM_LEDS];
+    for factory in registry::effects::<NUM_LEDS>() {
+        let mut effect = factory.create(&[]);
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            effect.before_render(DELTA);
+            for (i, led) in leds.iter_mut().enumerate() {
+                *led = effect.render(i, NUM_LEDS);
+            }
+        }
+        let per_frame = start.elapsed().as_micros() / FRAMES as u64;
+        // Printing a pixel keeps the renders from being optimised away.
+        println!("{:<12} {:>6} us/frame {:?}\r", factory.name, per_frame, leds[0]);
+    }
+
+    loop {}
+}
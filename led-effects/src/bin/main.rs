@@ -9,31 +9,95 @@
 use core::cell::RefCell;
 
 use critical_section::Mutex;
-// --- IMPORTS CHANGED ---
+use embassy_executor::Spawner;
+use embassy_net::{
+    tcp::TcpSocket,
+    udp::{PacketMetadata, UdpSocket},
+    Ipv4Address, Runner, Stack, StackResources,
+};
+use embassy_time::{Duration, Instant, Ticker, Timer};
 use esp_backtrace as _;
 use esp_hal::{
-    clock::CpuClock, delay::Delay, gpio::{Event, Input, InputConfig, Io, Pull}, handler, main, ram, rmt::Rmt, time::{Instant, Rate}
+    clock::CpuClock, efuse::Efuse, gpio::{interconnect::PeripheralOutput, Event, Input, InputConfig, Io, Level, Pull}, handler, ram,
+    rmt::{Error as RmtError, Rmt, TxChannel, TxChannelConfig, TxChannelCreator}, rng::Rng, time::Rate,
+    timer::{systimer::SystemTimer, timg::TimerGroup}, uart::{Config as UartConfig, Uart}, Async,
 };
 use esp_println::println;
-use esp_hal_smartled::{SmartLedsAdapter, smart_led_buffer};
-use led_effects::{controller::EffectController, drogen_effect::DrogenEffect, policedot_effect::PoliceDot, policetrail_effect::PoliceTrail, rocket_effect::RocketEffect};
-use led_effects::solid_effect::SolidColor;
-use smart_leds::{RGB8, SmartLedsWrite};
-
-extern crate alloc;
-use alloc::boxed::Box;
-// use alloc::vec::Vec; // No longer needed
+use esp_wifi::{
+    wifi::{self, ClientConfiguration, Configuration, WifiController, WifiDevice, WifiEvent, WifiState},
+    EspWifiController,
+};
+use static_cell::StaticCell;
+use led_effects::{controller::EffectController, registry};
+use led_effects::adalight::AdalightDecoder;
+use led_effects::dmx::{DmxMapping, DmxReceiver};
+use led_effects::e131::{multicast_address, E131Action, E131Packet, E131Receiver, E131_PORT};
+use led_effects::http_api::{self, ParseError, Request, Status};
+use led_effects::mqtt::{self, Topics, PAYLOAD_OFFLINE, PAYLOAD_ONLINE};
+use led_effects::output::{Chipset, ColorOrder, OutputConfig, Pixel, RGBW8};
+use led_effects::wled::DeviceInfo;
+use led_effects::stream::StreamTimeout;
+use led_effects::tpm2::Tpm2Decoder;
+use embedded_io_async::Write;
+use rust_mqtt::{
+    client::{
+        client::MqttClient,
+        client_config::{ClientConfig, MqttVersion},
+    },
+    packet::v5::{publish_packet::QualityOfService, reason_codes::ReasonCode},
+    utils::rng_generator::CountingRng,
+};
+use smart_leds::RGB8;
 
 // This creates a default app-descriptor required by the esp-idf bootloader.
 esp_bootloader_esp_idf::esp_app_desc!();
 static BUTTON: Mutex<RefCell<Option<Input>>> = Mutex::new(RefCell::new(None));
-static EFFECT_CONTROLLER: Mutex<RefCell<Option<EffectController>>> = Mutex::new(RefCell::new(None));
+static EFFECT_CONTROLLER: Mutex<RefCell<Option<EffectController<NUM_LEDS>>>> = Mutex::new(RefCell::new(None));
+static RING_CONTROLLER: Mutex<RefCell<Option<EffectController<RING_LEDS>>>> = Mutex::new(RefCell::new(None));
+// Last frame received from any streaming source (serial or E1.31).
+static STREAM: Mutex<RefCell<StreamFrame>> = Mutex::new(RefCell::new(StreamFrame {
+    leds: [RGB8::new(0, 0, 0); NUM_LEDS],
+    timeout: StreamTimeout::new(STREAM_TIMEOUT_SECS),
+}));
 
-const NUM_LEDS: usize = 16;
+static WIFI_CONTROLLER: StaticCell<EspWifiController<'static>> = StaticCell::new();
+static NET_RESOURCES: StaticCell<StackResources<5>> = StaticCell::new();
 
-// --- MAIN FUNCTION CHANGED TO BLOCKING ---
-#[main]
-fn main() -> ! {
+// Each output has its own RMT channel and effect controller. The strip is the
+// main output: streamed frames and the network APIs drive it.
+const NUM_LEDS: usize = 12; // strip on GPIO3
+const RING_LEDS: usize = 16; // ring on GPIO6
+// Chipset and colour order of each output. The ring is an SK6812 RGBW ring
+// and renders RGBW frames, effects without one get `RING.white` extracted.
+const STRIP: OutputConfig = OutputConfig::new(Chipset::Ws2812, ColorOrder::Grb);
+const RING: OutputConfig = OutputConfig::new(Chipset::Sk6812Rgbw, ColorOrder::Grb);
+// RMT clock with a divider of 1, a tick is 12.5 ns.
+const RMT_MHZ: u32 = 80;
+const FRAME_MS: u64 = 50;
+
+// Streaming input: DMX (Enttec framing), Adalight and TPM2 frames arrive on UART1.
+const STREAM_BAUDRATE: u32 = 250_000;
+// DMX channel of the red value of the first LED.
+const DMX_START_CHANNEL: usize = 1;
+// Seconds without frames before the local effects take over again.
+const STREAM_TIMEOUT_SECS: f32 = 2.0;
+
+// Wi-Fi credentials, set in the environment at build time.
+const SSID: &str = env!("SSID");
+const PASSWORD: &str = env!("PASSWORD");
+// E1.31 (sACN) universe and start channel mapped onto the strip.
+const E131_UNIVERSE: u16 = 1;
+const E131_START_CHANNEL: usize = 1;
+const HTTP_PORT: u16 = 80;
+// MQTT broker for Home Assistant, set in the environment at build time.
+const MQTT_BROKER: &str = env!("MQTT_BROKER");
+const MQTT_USERNAME: &str = env!("MQTT_USERNAME");
+const MQTT_PASSWORD: &str = env!("MQTT_PASSWORD");
+const MQTT_PORT: u16 = 1883;
+const DEVICE_NAME: &str = "LED Effects";
+
+#[esp_hal_embassy::main]
+async fn main(spawner: Spawner) {
     esp_println::logger::init_logger_from_env();
 
     let config = esp_hal::Config::default().with_cpu_clock(CpuClock::max());
@@ -49,77 +113,461 @@ fn main() -> ! {
         BUTTON.borrow_ref_mut(cs).replace(button)
     });
 
-    esp_alloc::heap_allocator!(size: 64 * 1024);
+    // Only esp-wifi needs the heap, the effects and controller are allocation-free.
+    // 72 KiB is the least esp-wifi runs on without coex, see its MIGRATING-0.9.md.
+    esp_alloc::heap_allocator!(size: 72 * 1024);
+
+    let timer0 = SystemTimer::new(peripherals.SYSTIMER);
+    esp_hal_embassy::init(timer0.alarm0);
 
     println!("Setup done.\r");
 
-    // --- RMT INITIALIZATION CHANGED TO BLOCKING ---
     let rmt: Rmt<'_, esp_hal::Blocking> = {
-        let frequency: Rate = Rate::from_mhz(80);
+        let frequency: Rate = Rate::from_mhz(RMT_MHZ);
         Rmt::new(peripherals.RMT, frequency)
     }
     .expect("Failed to initialize RMT");
 
-    let rmt_channel = rmt.channel0;
-
-    // --- BUFFER AND LED ADAPTER CHANGED TO BLOCKING VERSIONS ---
-    let rmt_buffer = smart_led_buffer!(NUM_LEDS);
-    let mut led = SmartLedsAdapter::new(rmt_channel, peripherals.GPIO3, rmt_buffer);
+    let mut led: RmtOutput<_, { STRIP.rmt_len(NUM_LEDS) }> =
+        RmtOutput::new(rmt.channel0, peripherals.GPIO3, STRIP);
+    let mut ring: RmtOutput<_, { RING.rmt_len(RING_LEDS) }> =
+        RmtOutput::new(rmt.channel1, peripherals.GPIO6, RING);
     
     println!("LED Setup done.\r");
 
+    let uart = Uart::new(
+        peripherals.UART1,
+        UartConfig::default().with_baudrate(STREAM_BAUDRATE),
+    )
+    .expect("Failed to initialize UART")
+    .with_rx(peripherals.GPIO4)
+    .with_tx(peripherals.GPIO5)
+    .into_async();
+
+    spawner.must_spawn(serial_stream(uart));
+
+    println!("Stream Setup done.\r");
+
+    let mut rng = Rng::new(peripherals.RNG);
+    let timg0 = TimerGroup::new(peripherals.TIMG0);
+    let wifi_init = WIFI_CONTROLLER.init(
+        esp_wifi::init(timg0.timer0, rng).expect("Failed to initialize Wi-Fi"),
+    );
+    let (wifi_controller, interfaces) =
+        wifi::new(wifi_init, peripherals.WIFI).expect("Failed to create Wi-Fi controller");
+
+    let seed = (rng.random() as u64) << 32 | rng.random() as u64;
+    let (stack, runner) = embassy_net::new(
+        interfaces.sta,
+        embassy_net::Config::dhcpv4(Default::default()),
+        NET_RESOURCES.init(StackResources::new()),
+        seed,
+    );
+
+    spawner.must_spawn(wifi_connection(wifi_controller));
+    spawner.must_spawn(net_task(runner));
+    spawner.must_spawn(e131_stream(stack));
+    spawner.must_spawn(http_server(stack));
+    spawner.must_spawn(mqtt_client(stack));
+
+    println!("Wi-Fi Setup done.\r");
+
     let mut leds: [RGB8; NUM_LEDS] = [RGB8::default(); NUM_LEDS];
+    let mut ring_leds: [RGBW8; RING_LEDS] = [RGBW8::default(); RING_LEDS];
     let mut last_update = Instant::now();
 
-    let mut effect_controller = EffectController::new();
-    effect_controller.add_effect(Box::new(SolidColor {
-        color: RGB8::new(255, 0, 0),
-    }));
-    effect_controller.add_effect(Box::new(PoliceDot::new(1.0, 2, NUM_LEDS)));
-    effect_controller.add_effect(Box::new(PoliceTrail::new(1.0, 2, 8, NUM_LEDS)));
-    effect_controller.add_effect(Box::new(DrogenEffect::new(NUM_LEDS)));
-    effect_controller.add_effect(Box::new(RocketEffect::new(NUM_LEDS)));
-    effect_controller.set_effect_by_name("PoliceTrail");
-
     critical_section::with(|cs| {
-        EFFECT_CONTROLLER.borrow_ref_mut(cs).replace(effect_controller);
+        EFFECT_CONTROLLER
+            .borrow_ref_mut(cs)
+            .replace(new_controller("PoliceTrail"));
+        RING_CONTROLLER
+            .borrow_ref_mut(cs)
+            .replace(new_controller("Drogen"));
     });
-    
-    let delay = Delay::new();
 
-    // --- LOOP CHANGED TO USE BLOCKING CALLS ---
+    let mut ticker = Ticker::every(Duration::from_millis(FRAME_MS));
+
     loop {
         let now = Instant::now();
        let delta = (now - last_update).as_millis() as f32 / 1000.0;
         last_update = now;
 
-        critical_section::with(|cs| {
-            if let Some(controller) = EFFECT_CONTROLLER.borrow_ref_mut(cs).as_mut() {
-                                let current_effect = controller.get_current_effect();
-                current_effect.before_render(delta);
+        let streaming = critical_section::with(|cs| {
+            let mut stream = STREAM.borrow_ref_mut(cs);
+            stream.timeout.update(delta);
+            if stream.timeout.is_active() {
+                leds = stream.leds;
+            }
+            stream.timeout.is_active()
+        });
+
+        with_controller(|controller| {
+            if streaming {
+                controller.apply_output_level(&mut leds);
+            } else {
+                controller.render_output(delta, &mut leds);
+            }
+        });
+        with_output(&RING_CONTROLLER, |controller| {
+            controller.render_rgbw(delta, RING.white, &mut ring_leds);
+            controller.apply_output_level_rgbw(&mut ring_leds);
+        });
+
+        // The RMT writes are blocking, the other tasks run while we wait for the next tick.
+        led.write(&leds).unwrap();
+        ring.write(&ring_leds).unwrap();
+
+        ticker.next().await;
+    }
+}
+
+/// An LED output on an RMT channel, encoding frames for its chipset.
+struct RmtOutput<Tx, const LEN: usize> {
+    channel: Option<Tx>,
+    config: OutputConfig,
+    buffer: [u32; LEN],
+}
+
+impl<'d, Tx: TxChannel, const LEN: usize> RmtOutput<Tx, LEN> {
+    fn new(
+        channel: impl TxChannelCreator<'d, Tx>,
+        pin: impl PeripheralOutput<'d>,
+        config: OutputConfig,
+    ) -> Self {
+        let channel_config = TxChannelConfig::default()
+            .with_clk_divider(1)
+            .with_idle_output_level(Level::Low)
+            .with_carrier_modulation(false)
+            .with_idle_output(true);
+        let channel = channel
+            .configure_tx(pin, channel_config)
+            .expect("Failed to configure RMT channel");
+        Self {
+            channel: Some(channel),
+            config,
+            buffer: [0; LEN],
+        }
+    }
+
+    /// Sends `leds`, blocking until the frame is out.
+    fn write<P: Pixel>(&mut self, leds: &[P]) -> Result<(), RmtError> {
+        let len = self
+            .config
+            .encode_rmt(leds.iter().copied(), RMT_MHZ, &mut self.buffer)
+            .expect("RMT outputs need a clockless chipset and a buffer of rmt_len()");
+        let channel = self.channel.take().expect("RMT channel lost in an earlier write");
+        match channel.transmit(&self.buffer[..len])?.wait() {
+            Ok(channel) => {
+                self.channel = Some(channel);
+                Ok(())
+            }
+            Err((e, channel)) => {
+                self.channel = Some(channel);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Runs `f` on the effect controller shared with the button handler and
+/// the network tasks.
+fn with_controller<R>(f: impl FnOnce(&mut EffectController<NUM_LEDS>) -> R) -> Option<R> {
+    with_output(&EFFECT_CONTROLLER, f)
+}
+
+/// Runs `f` on the effect controller of an output.
+fn with_output<const N: usize, R>(
+    controller: &Mutex<RefCell<Option<EffectController<N>>>>,
+    f: impl FnOnce(&mut EffectController<N>) -> R,
+) -> Option<R> {
+    critical_section::with(|cs| controller.borrow_ref_mut(cs).as_mut().map(f))
+}
+
+/// Creates a controller with all built-in effects, starting with `effect`.
+fn new_controller<const N: usize>(effect: &str) -> EffectController<N> {
+    let mut controller = EffectController::new();
+    for factory in registry::effects() {
+        controller
+            .register(factory)
+            .expect("Effect names must be unique");
+    }
+    controller
+        .set_effect_by_name(effect)
+        .expect("Starting effect must be built in");
+    controller
+}
+
+struct StreamFrame {
+    leds: [RGB8; NUM_LEDS],
+    timeout: StreamTimeout,
+}
+
+/// Publishes a frame from a streaming source to the render loop.
+fn show_stream_frame(pixel: impl Fn(usize) -> RGB8) {
+    critical_section::with(|cs| {
+        let mut stream = STREAM.borrow_ref_mut(cs);
+        for (i, led) in stream.leds.iter_mut().enumerate() {
+            *led = pixel(i);
+        }
+        stream.timeout.frame_received();
+    });
+}
+
+/// Decodes DMX (Enttec framing), Adalight and TPM2 frames from the stream
+/// UART. Every byte is fed to all decoders, whichever protocol the sender
+/// speaks completes its frames.
+#[embassy_executor::task]
+async fn serial_stream(mut uart: Uart<'static, Async>) {
+    let mut dmx = DmxReceiver::new();
+    let dmx_mapping = DmxMapping::new(DMX_START_CHANNEL);
+    let mut adalight = AdalightDecoder::<NUM_LEDS>::new();
+    let mut tpm2 = Tpm2Decoder::<NUM_LEDS>::new();
+
+    let mut buf = [0u8; 64];
+    loop {
+        let Ok(count) = uart.read_async(&mut buf).await else {
+            continue;
+        };
+        for &byte in &buf[..count] {
+            if dmx.feed(byte) {
+                show_stream_frame(|i| dmx_mapping.pixel(dmx.channels(), i));
+            }
+            if adalight.feed(byte) {
+                show_stream_frame(|i| adalight.pixels()[i]);
+            }
+            if tpm2.feed(byte) {
+                show_stream_frame(|i| tpm2.pixels()[i]);
+            }
+        }
+    }
+}
+
+/// Listens for E1.31 packets on the configured universe, both multicast and
+/// unicast.
+#[embassy_executor::task]
+async fn e131_stream(stack: Stack<'static>) {
+    let mut receiver = E131Receiver::new(E131_UNIVERSE, E131_START_CHANNEL);
+
+    let mut rx_meta = [PacketMetadata::EMPTY; 4];
+    let mut rx_buffer = [0u8; 1024];
+    let mut tx_meta = [PacketMetadata::EMPTY; 1];
+    let mut tx_buffer = [0u8; 16];
+    let mut packet = [0u8; 638];
+
+    stack.wait_config_up().await;
+    if let Some(config) = stack.config_v4() {
+        println!("Got IP: {}\r", config.address);
+    }
+
+    let [a, b, c, d] = multicast_address(E131_UNIVERSE);
+    if stack.join_multicast_group(Ipv4Address::new(a, b, c, d)).is_err() {
+        println!("Failed to join E1.31 multicast group, unicast only\r");
+    }
+
+    let mut socket = UdpSocket::new(stack, &mut rx_meta, &mut rx_buffer, &mut tx_meta, &mut tx_buffer);
+    socket.bind(E131_PORT).expect("Failed to bind E1.31 socket");
+
+    loop {
+        let Ok((len, _)) = socket.recv_from(&mut packet).await else {
+            continue;
+        };
+        let Ok(e131) = E131Packet::parse(&packet[..len]) else {
+            continue;
+        };
+        match receiver.accept(&e131) {
+            E131Action::Frame => show_stream_frame(|i| receiver.pixel(&e131, i)),
+            E131Action::Stop => critical_section::with(|cs| STREAM.borrow_ref_mut(cs).timeout.stop()),
+            E131Action::Ignore => {}
+        }
+    }
+}
+
+/// Serves the HTTP/JSON control API, one connection at a time.
+#[embassy_executor::task]
+async fn http_server(stack: Stack<'static>) {
+    let mut rx_buffer = [0u8; 1024];
+    let mut tx_buffer = [0u8; 1024];
+    let mut request = [0u8; http_api::MAX_REQUEST];
+    let mut response = [0u8; 1536];
+    let device = DeviceInfo {
+        name: DEVICE_NAME,
+        num_leds: NUM_LEDS,
+        mac: Efuse::mac_address(),
+    };
+
+    stack.wait_config_up().await;
+
+    loop {
+        let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+        socket.set_timeout(Some(Duration::from_secs(10)));
+
+        if socket.accept(HTTP_PORT).await.is_err() {
+            continue;
+        }
 
-                for i in 0..NUM_LEDS {
-                    leds[i] = current_effect.render(i, NUM_LEDS);
+        let mut received = 0;
+        let response_len = loop {
+            match socket.read(&mut request[received..]).await {
+                Ok(0) | Err(_) => break None,
+                Ok(count) => received += count,
+            }
+            match Request::parse(&request[..received]) {
+                Ok(parsed) => {
+                    break with_controller(|controller| {
+                        http_api::respond(controller, &device, &parsed, &mut response)
+                    });
                 }
+                Err(ParseError::Incomplete) if received < request.len() => {}
+                Err(_) => break Some(http_api::respond_error(Status::BadRequest, &mut response)),
             }
-        });        
+        };
 
-        // let current_effect = effect_controller.get_current_effect();
-        // current_effect.before_render(delta);
+        if let Some(len) = response_len {
+            let _ = socket.write_all(&response[..len]).await;
+            let _ = socket.flush().await;
+        }
+        socket.close();
+        Timer::after(Duration::from_millis(50)).await;
+        socket.abort();
+    }
+}
+
+/// Connects to the MQTT broker, announces the light to Home Assistant and
+/// keeps command and state topics in sync, reconnecting when the broker
+/// goes away.
+#[embassy_executor::task]
+async fn mqtt_client(stack: Stack<'static>) {
+    let node_id = mqtt::node_id(Efuse::mac_address());
+    let topics = Topics::new(&node_id);
+    let broker: Ipv4Address = MQTT_BROKER.parse().expect("MQTT_BROKER is not an IPv4 address");
 
-        // for i in 0..NUM_LEDS {
-        //     leds[i] = current_effect.render(i, NUM_LEDS);
-        // }
+    let mut rx_buffer = [0u8; 1024];
+    let mut tx_buffer = [0u8; 1024];
+    let mut write_buffer = [0u8; 1024];
+    let mut recv_buffer = [0u8; 1024];
 
-        // --- WRITE CALL IS NOW BLOCKING (NO .await) ---
-        led.write(leds.iter().cloned()).unwrap();
+    stack.wait_config_up().await;
+
+    loop {
+        let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+        if socket.connect((broker, MQTT_PORT)).await.is_err() {
+            println!("MQTT broker unreachable\r");
+            Timer::after(Duration::from_secs(5)).await;
+            continue;
+        }
 
-        // --- DELAY IS NOW BLOCKING ---
-        //delay.delay_ms(1000u32);
-        delay.delay_millis(50u32);
+        let mut config = ClientConfig::new(MqttVersion::MQTTv5, CountingRng(20000));
+        config.add_client_id(&node_id);
+        config.add_will(&topics.availability, PAYLOAD_OFFLINE.as_bytes(), true);
+        if !MQTT_USERNAME.is_empty() {
+            config.add_username(MQTT_USERNAME);
+            config.add_password(MQTT_PASSWORD);
+        }
+        config.max_packet_size = 1024;
+        config.keep_alive = 60;
+
+        let mut client = MqttClient::<_, 5, _>::new(
+            socket,
+            &mut write_buffer,
+            1024,
+            &mut recv_buffer,
+            1024,
+            config,
+        );
+        if let Err(e) = mqtt_session(&mut client, &node_id, &topics).await {
+            println!("MQTT session ended: {:?}\r", e);
+        }
+        Timer::after(Duration::from_secs(5)).await;
     }
 }
 
+async fn mqtt_session(
+    client: &mut MqttClient<'_, TcpSocket<'_>, 5, CountingRng>,
+    node_id: &str,
+    topics: &Topics,
+) -> Result<(), ReasonCode> {
+    let mut payload = [0u8; 768];
+    let mut state = [0u8; 256];
+    let mut published = [0u8; 256];
+    let mut published_len = 0;
+    let mut last_ping = Instant::now();
+
+    client.connect_to_broker().await?;
+
+    let len = with_controller(|controller| {
+        mqtt::discovery_config(controller, node_id, DEVICE_NAME, topics, &mut payload)
+    })
+    .and_then(Result::ok)
+    .ok_or(ReasonCode::ImplementationSpecificError)?;
+    client
+        .send_message(&topics.config, &payload[..len], QualityOfService::QoS0, true)
+        .await?;
+    client
+        .send_message(&topics.availability, PAYLOAD_ONLINE.as_bytes(), QualityOfService::QoS0, true)
+        .await?;
+    client.subscribe_to_topic(&topics.command).await?;
+
+    loop {
+        // Publish the state whenever it changed, whether through MQTT, HTTP or the button.
+        let state_len =
+            with_controller(|controller| mqtt::state(controller, &mut state)).and_then(Result::ok);
+        if let Some(len) = state_len {
+            if state[..len] != published[..published_len] {
+                client
+                    .send_message(&topics.state, &state[..len], QualityOfService::QoS0, true)
+                    .await?;
+                published[..len].copy_from_slice(&state[..len]);
+                published_len = len;
+            }
+        }
+
+        // Reads a packet only once it started arriving, and then to its end:
+        // a receive cut short by a timeout would leave the session out of sync.
+        match client.receive_message_if_ready().await? {
+            Some((_, message)) => {
+                if let Some(Err(e)) = with_controller(|controller| mqtt::apply_command(controller, message)) {
+                    println!("Rejected MQTT command: {:?}\r", e);
+                }
+            }
+            None => Timer::after(Duration::from_millis(100)).await,
+        }
+
+        if last_ping.elapsed() > Duration::from_secs(30) {
+            client.send_ping().await?;
+            last_ping = Instant::now();
+        }
+    }
+}
+
+/// Keeps the station connected, reconnecting after drops.
+#[embassy_executor::task]
+async fn wifi_connection(mut controller: WifiController<'static>) {
+    loop {
+        if wifi::wifi_state() == WifiState::StaConnected {
+            controller.wait_for_event(WifiEvent::StaDisconnected).await;
+            Timer::after(Duration::from_millis(5000)).await;
+        }
+        if !matches!(controller.is_started(), Ok(true)) {
+            let client_config = Configuration::Client(ClientConfiguration {
+                ssid: SSID.into(),
+                password: PASSWORD.into(),
+                ..Default::default()
+            });
+            controller.set_configuration(&client_config).unwrap();
+            controller.start_async().await.unwrap();
+        }
+        if let Err(e) = controller.connect_async().await {
+            println!("Wi-Fi connect failed: {:?}\r", e);
+            Timer::after(Duration::from_millis(5000)).await;
+        }
+    }
+}
+
+#[embassy_executor::task]
+async fn net_task(mut runner: Runner<'static, WifiDevice<'static>>) {
+    runner.run().await
+}
+
 #[handler]
 #[ram]
 fn handler() {
@@ -134,14 +582,12 @@ fn handler() {
     });
 
     if button_pressed {
-        // --- On button press, access the controller and change the effect ---
-        critical_section::with(|cs| {
-            // Borrow the controller mutably
-            if let Some(controller) = EFFECT_CONTROLLER.borrow_ref_mut(cs).as_mut() {
-                // Change to the next effect in the list
-                controller.next_effect();
-               
-            }
+        // Change the effect on every output, nothing to do for an output without effects.
+        with_output(&EFFECT_CONTROLLER, |controller| {
+            let _ = controller.next_effect();
+        });
+        with_output(&RING_CONTROLLER, |controller| {
+            let _ = controller.next_effect();
         });
     }    
 }
@@ -1,42 +1,390 @@
 use crate::effect::LedEffect; 
-extern crate alloc;
-use alloc::boxed::Box;
-use alloc::vec::Vec;
+use crate::output::{WhiteMode, RGB16, RGBW8};
+use crate::palette::{Palette, PALETTES};
+use crate::registry::{Effect, EffectFactory};
+use heapless::Vec;
+use smart_leds::{White, RGB8};
 
+/// Number of effects a controller can register.
+pub const MAX_EFFECTS: usize = 16;
 
-pub struct EffectController {
-    effects: Vec<Box<dyn LedEffect + Send>>,
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerError {
+    /// No registered effect has that name or index.
+    UnknownEffect,
+    /// No effect is registered.
+    Empty,
+    /// An effect of that name is already registered.
+    DuplicateName,
+    /// `MAX_EFFECTS` effects are already registered.
+    Full,
+}
+
+/// Switches between registered effects. Only the current effect is
+/// constructed; switching away drops it and its parameter changes.
+///
+/// `N` is the number of LEDs, the controller itself never allocates.
+pub struct EffectController<const N: usize> {
+    effects: Vec<&'static EffectFactory<N>, MAX_EFFECTS>,
+    current_effect: Option<Effect<N>>,
     current_effect_index: usize,
+    brightness: u8,
+    on: bool,
+    palette_index: usize,
+    /// The last output frame before quantizing, 8.8 fixed point.
+    frame: [RGB16; N],
+    /// Low bytes of `frame` not shown yet, carried into the next frame.
+    dither: [[u8; 4]; N],
+}
+
+impl<const N: usize> Default for EffectController<N> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl EffectController {
-    pub fn new() -> Self {
+impl<const N: usize> EffectController<N> {
+    pub const fn new() -> Self {
         Self {
             effects: Vec::new(),
+            current_effect: None,
             current_effect_index: 0,
+            brightness: 255,
+            on: true,
+            palette_index: 0,
+            frame: [RGB16 { r: 0, g: 0, b: 0 }; N],
+            dither: [[0; 4]; N],
         }
     }
 
-    pub fn add_effect(&mut self, effect: Box<dyn LedEffect + Send + 'static>) {
-        self.effects.push(effect);
+    /// Makes an effect selectable, the first one registered becomes current.
+    /// Effect names must be unique.
+    pub fn register(&mut self, factory: &'static EffectFactory<N>) -> Result<(), ControllerError> {
+        if self.index_of(factory.name).is_ok() {
+            return Err(ControllerError::DuplicateName);
+        }
+        self.effects
+            .push(factory)
+            .map_err(|_| ControllerError::Full)?;
+        if self.current_effect.is_none() {
+            self.select(0);
+        }
+        Ok(())
+    }
+
+    pub fn next_effect(&mut self) -> Result<(), ControllerError> {
+        let count = self.len_or_empty()?;
+        self.select((self.current_effect_index + 1) % count);
+        Ok(())
+    }
+
+    pub fn previous_effect(&mut self) -> Result<(), ControllerError> {
+        let count = self.len_or_empty()?;
+        self.select((self.current_effect_index + count - 1) % count);
+        Ok(())
     }
 
-    pub fn next_effect(&mut self) {
-        self.current_effect_index = (self.current_effect_index + 1) % self.effects.len();
+    pub fn get_current_effect(&mut self) -> Result<&mut dyn LedEffect, ControllerError> {
+        match self.current_effect.as_mut() {
+            Some(effect) => Ok(effect),
+            None => Err(ControllerError::Empty),
+        }
+    }
+
+    /// Number of registered effects.
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    fn len_or_empty(&self) -> Result<usize, ControllerError> {
+        match self.effects.len() {
+            0 => Err(ControllerError::Empty),
+            count => Ok(count),
+        }
+    }
+
+    /// Creates the effect at `index` unless it is already current.
+    fn select(&mut self, index: usize) {
+        if index == self.current_effect_index && self.current_effect.is_some() {
+            return;
+        }
+        let mut effect = self.effects[index].create(&[]);
+        effect.set_palette(self.palette());
+        self.current_effect = Some(effect);
+        self.current_effect_index = index;
     }
 
-    pub fn get_current_effect(&mut self) -> &mut dyn LedEffect {
-        self.effects[self.current_effect_index].as_mut()
+    /// Index of the current effect, in the order effects were registered.
+    pub fn current_effect_index(&self) -> usize {
+        self.current_effect_index
     }
 
-    pub fn set_effect_by_name(&mut self, name: &str)  {
-        for (i, effect) in self.effects.iter().enumerate() {
-            if effect.name() == name {
-                self.current_effect_index = i;
-                
-            }
+    pub fn set_effect_by_index(&mut self, index: usize) -> Result<(), ControllerError> {
+        if index >= self.len_or_empty()? {
+            return Err(ControllerError::UnknownEffect);
         }
-        
-    }    
+        self.select(index);
+        Ok(())
+    }
+
+    pub fn set_effect_by_name(&mut self, name: &str) -> Result<(), ControllerError> {
+        let index = self.index_of(name)?;
+        self.select(index);
+        Ok(())
+    }
+
+    pub fn current_effect(&self) -> Result<&dyn LedEffect, ControllerError> {
+        match self.current_effect.as_ref() {
+            Some(effect) => Ok(effect),
+            None => Err(ControllerError::Empty),
+        }
+    }
+
+    /// Name of the current effect.
+    pub fn current_effect_name(&self) -> Result<&'static str, ControllerError> {
+        match self.effects.get(self.current_effect_index) {
+            Some(factory) => Ok(factory.name),
+            None => Err(ControllerError::Empty),
+        }
+    }
+
+    /// Index of the registered effect called `name`.
+    pub fn index_of(&self, name: &str) -> Result<usize, ControllerError> {
+        self.effects
+            .iter()
+            .position(|factory| factory.name == name)
+            .ok_or(ControllerError::UnknownEffect)
+    }
+
+    /// Name of the registered effect at `index`.
+    pub fn effect_name(&self, index: usize) -> Result<&'static str, ControllerError> {
+        self.effects
+            .get(index)
+            .map(|factory| factory.name)
+            .ok_or(ControllerError::UnknownEffect)
+    }
+
+    /// Factories of the registered effects, in the order they were registered.
+    pub fn effects(&self) -> impl Iterator<Item = &'static EffectFactory<N>> + '_ {
+        self.effects.iter().copied()
+    }
+
+    /// Names of the registered effects, in the order they were registered.
+    pub fn effect_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.effects.iter().map(|factory| factory.name)
+    }
+
+    /// Global brightness applied to the strip output, 255 is full brightness.
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.brightness = brightness;
+    }
+
+    /// Index into `PALETTES` of the selected palette, 0 when effects use their own colours.
+    pub fn palette_index(&self) -> usize {
+        self.palette_index
+    }
+
+    /// Selects a palette from `PALETTES`, returns false for an unknown index.
+    pub fn set_palette(&mut self, index: usize) -> bool {
+        if index >= PALETTES.len() {
+            return false;
+        }
+        self.palette_index = index;
+        let palette = self.palette();
+        if let Some(effect) = self.current_effect.as_mut() {
+            effect.set_palette(palette);
+        }
+        true
+    }
+
+    fn palette(&self) -> Option<&'static Palette> {
+        match self.palette_index {
+            0 => None,
+            index => Some(&PALETTES[index]),
+        }
+    }
+
+    /// Advances the current effect by `delta` seconds and renders all `N`
+    /// LEDs into `leds`. Without effects `leds` is left untouched.
+    pub fn render(&mut self, delta: f32, leds: &mut [RGB8; N]) {
+        let Ok(effect) = self.get_current_effect() else {
+            return;
+        };
+        effect.before_render(delta);
+        for (i, led) in leds.iter_mut().enumerate() {
+            *led = effect.render(i, N);
+        }
+    }
+
+    /// Like `render` for RGBW strips. Effects without an RGBW rendering get
+    /// their white channel extracted with `white`, usually the `white` of
+    /// the output's `OutputConfig`.
+    pub fn render_rgbw(&mut self, delta: f32, white: WhiteMode, leds: &mut [RGBW8; N]) {
+        let Ok(effect) = self.get_current_effect() else {
+            return;
+        };
+        effect.before_render(delta);
+        for (i, led) in leds.iter_mut().enumerate() {
+            *led = effect
+                .render_rgbw(i, N)
+                .unwrap_or_else(|| white.extract(effect.render(i, N)));
+        }
+    }
+
+    /// Level to scale the output by: the brightness, or 0 when switched off.
+    pub fn output_level(&self) -> u8 {
+        if self.on {
+            self.brightness
+        } else {
+            0
+        }
+    }
+
+    /// Like `render`, but renders the effect at 16 bit into `frame()`,
+    /// scales it by `output_level()` and dithers it into `leds`, so the fades
+    /// of effects with a `render16`, such as the trails of `PoliceTrail` and
+    /// `Rocket`, keep their steps below 8 bit. Without effects `leds` is
+    /// scaled as by `apply_output_level`.
+    pub fn render_output(&mut self, delta: f32, leds: &mut [RGB8; N]) {
+        let scale = self.output_scale() as u32;
+        let Some(effect) = self.current_effect.as_mut() else {
+            self.apply_output_level(leds);
+            return;
+        };
+        effect.before_render(delta);
+        let scaled = |value: u16| ((value as u32 * scale) >> 8) as u16;
+        for (i, frame) in self.frame.iter_mut().enumerate() {
+            let color = effect.render16(i, N);
+            *frame = RGB16 {
+                r: scaled(color.r),
+                g: scaled(color.g),
+                b: scaled(color.b),
+            };
+        }
+        self.quantize(leds);
+    }
+
+    /// Scales `leds` by `output_level()` with temporal dithering. The scaled
+    /// frame is kept at 16 bit and what rounding down loses is carried into
+    /// the next frame, so a colour too dim for 8 bit still shows on average
+    /// and the brightness steps do not show at low levels.
+    ///
+    /// This is for frames from elsewhere, such as streams; effects go through
+    /// `render_output` so their own fades are dithered too.
+    pub fn apply_output_level(&mut self, leds: &mut [RGB8; N]) {
+        let scale = self.output_scale();
+        for (led, frame) in leds.iter().zip(&mut self.frame) {
+            *frame = RGB16 {
+                r: led.r as u16 * scale,
+                g: led.g as u16 * scale,
+                b: led.b as u16 * scale,
+            };
+        }
+        self.quantize(leds);
+    }
+
+    /// Dithers `frame` down to 8 bit into `leds`.
+    fn quantize(&mut self, leds: &mut [RGB8; N]) {
+        for ((led, frame), carry) in leds.iter_mut().zip(&self.frame).zip(&mut self.dither) {
+            *led = RGB8::new(
+                dither(frame.r, &mut carry[0]),
+                dither(frame.g, &mut carry[1]),
+                dither(frame.b, &mut carry[2]),
+            );
+        }
+    }
+
+    /// Like `apply_output_level` for frames of `render_rgbw`. The white
+    /// channel is dithered too, `frame()` keeps the colour channels only.
+    pub fn apply_output_level_rgbw(&mut self, leds: &mut [RGBW8; N]) {
+        let scale = self.output_scale();
+        for ((led, frame), carry) in leds.iter_mut().zip(&mut self.frame).zip(&mut self.dither) {
+            *frame = RGB16 {
+                r: led.r as u16 * scale,
+                g: led.g as u16 * scale,
+                b: led.b as u16 * scale,
+            };
+            let white = led.a.0 as u16 * scale;
+            *led = RGBW8 {
+                r: dither(frame.r, &mut carry[0]),
+                g: dither(frame.g, &mut carry[1]),
+                b: dither(frame.b, &mut carry[2]),
+                a: White(dither(white, &mut carry[3])),
+            };
+        }
+    }
+
+    /// `output_level()` as a factor where 256 keeps a colour as it is.
+    fn output_scale(&self) -> u16 {
+        // A level of 255 keeps 255 at 255, as `scale8` does, and 0 is dark.
+        match self.output_level() {
+            0 => 0,
+            level => level as u16 + 1,
+        }
+    }
+
+    /// The last frame scaled by `render_output` or `apply_output_level`, 256
+    /// is one 8 bit step.
+    pub fn frame(&self) -> &[RGB16; N] {
+        &self.frame
+    }
+
+    /// Whether the strip is switched on; when off the output is blanked but
+    /// the effect and brightness are kept.
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    pub fn set_on(&mut self, on: bool) {
+        self.on = on;
+    }
+
+    /// Colour of the current effect, if it has `red`, `green` and `blue` parameters.
+    pub fn color(&self) -> Option<RGB8> {
+        let effect = self.current_effect().ok()?;
+        Some(RGB8::new(
+            effect.parameter("red")? as u8,
+            effect.parameter("green")? as u8,
+            effect.parameter("blue")? as u8,
+        ))
+    }
+
+    /// Applies `color` through the `red`, `green` and `blue` parameters of the
+    /// current effect, switching to the first effect that has them if the
+    /// current one does not. Returns false if no effect takes a colour.
+    pub fn set_color(&mut self, color: RGB8) -> bool {
+        if self.color().is_none() {
+            let Some(index) = self
+                .effects
+                .iter()
+                .position(|factory| factory.has_parameter("red"))
+            else {
+                return false;
+            };
+            self.select(index);
+        }
+        let Ok(effect) = self.get_current_effect() else {
+            return false;
+        };
+        effect.set_parameter("red", color.r as f32);
+        effect.set_parameter("green", color.g as f32);
+        effect.set_parameter("blue", color.b as f32);
+        true
+    }
 }
 
+/// High byte of `value` plus the carry, keeping the new low byte as carry.
+fn dither(value: u16, carry: &mut u8) -> u8 {
+    let value = value as u32 + *carry as u32;
+    *carry = value as u8;
+    (value >> 8).min(255) as u8
+}
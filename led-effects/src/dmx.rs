@@ -0,0 +1,146 @@
+use smart_leds::RGB8;
+
+/// Maximum number of channels in a DMX512 universe.
+pub const DMX_UNIVERSE_SIZE: usize = 512;
+
+const START_OF_MESSAGE: u8 = 0x7E;
+const END_OF_MESSAGE: u8 = 0xE7;
+/// "Output Only Send DMX Packet" label of the Enttec DMX USB Pro protocol.
+const LABEL_SEND_DMX: u8 = 6;
+/// DMX start code for regular dimmer (channel level) data.
+const START_CODE_DIMMER: u8 = 0x00;
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Idle,
+    Label,
+    LengthLow,
+    LengthHigh,
+    Data,
+    End,
+}
+
+/// Byte-at-a-time parser for DMX universes sent over a serial link.
+///
+/// Frames use the Enttec DMX USB Pro framing that most lighting desks and
+/// DMX bridges can emit:
+///
+/// ```text
+/// 0x7E | label | len lo | len hi | start code | channel 1 .. channel n | 0xE7
+/// ```
+///
+/// `len` counts the start code plus the channel values, so the universe size
+/// of a frame is `len - 1`. Only label 6 frames with the dimmer start code are
+/// accepted; anything else is skipped.
+pub struct DmxReceiver {
+    state: State,
+    label: u8,
+    length: usize,
+    received: usize,
+    start_code: u8,
+    channels: [u8; DMX_UNIVERSE_SIZE],
+    universe_size: usize,
+}
+
+impl DmxReceiver {
+    pub fn new() -> Self {
+        Self {
+            state: State::Idle,
+            label: 0,
+            length: 0,
+            received: 0,
+            start_code: 0,
+            channels: [0; DMX_UNIVERSE_SIZE],
+            universe_size: 0,
+        }
+    }
+
+    /// Feeds one received byte into the parser.
+    /// Returns true when the byte completed a valid frame, which can then be
+    /// read with `channels()` until the next byte is fed.
+    pub fn feed(&mut self, byte: u8) -> bool {
+        match self.state {
+            State::Idle => {
+                if byte == START_OF_MESSAGE {
+                    self.state = State::Label;
+                }
+            }
+            State::Label => {
+                self.label = byte;
+                self.state = State::LengthLow;
+            }
+            State::LengthLow => {
+                self.length = byte as usize;
+                self.state = State::LengthHigh;
+            }
+            State::LengthHigh => {
+                self.length |= (byte as usize) << 8;
+                self.received = 0;
+                self.state = match self.length {
+                    0 => State::End,
+                    // Longer than a start code plus a full universe: not a frame we know.
+                    len if len > DMX_UNIVERSE_SIZE + 1 => State::Idle,
+                    _ => State::Data,
+                };
+            }
+            State::Data => {
+                if self.received == 0 {
+                    self.start_code = byte;
+                } else {
+                    self.channels[self.received - 1] = byte;
+                }
+                self.received += 1;
+                if self.received == self.length {
+                    self.state = State::End;
+                }
+            }
+            State::End => {
+                self.state = State::Idle;
+                if byte == END_OF_MESSAGE
+                    && self.label == LABEL_SEND_DMX
+                    && self.length > 0
+                    && self.start_code == START_CODE_DIMMER
+                {
+                    self.universe_size = self.length - 1;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Channel values of the last completed frame.
+    pub fn channels(&self) -> &[u8] {
+        &self.channels[..self.universe_size]
+    }
+}
+
+impl Default for DmxReceiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Maps DMX channels onto the strip, three channels (R, G, B) per LED.
+#[derive(Clone, Copy)]
+pub struct DmxMapping {
+    /// DMX channel (1-based, as numbered on a lighting desk) holding the red
+    /// value of the first LED.
+    pub start_channel: usize,
+}
+
+impl DmxMapping {
+    pub fn new(start_channel: usize) -> Self {
+        Self { start_channel }
+    }
+
+    /// Returns the colour of LED `index`, or black if the universe is too
+    /// small to cover it.
+    pub fn pixel(&self, channels: &[u8], index: usize) -> RGB8 {
+        let first = self.start_channel.saturating_sub(1) + index * 3;
+        match channels.get(first..first + 3) {
+            Some(rgb) => RGB8::new(rgb[0], rgb[1], rgb[2]),
+            None => RGB8::default(),
+        }
+    }
+}
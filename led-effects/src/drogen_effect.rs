@@ -1,17 +1,24 @@
 use smart_leds::RGB8;
 use crate::effect::LedEffect;
+use crate::palette::Palette;
+#[cfg(feature = "fixed-point")]
+use crate::fixed::{hsv8, sin8, Q16};
+#[cfg(not(feature = "fixed-point"))]
 use libm::{fabsf, sinf};
+#[cfg(not(feature = "fixed-point"))]
 use core::f32::consts::PI;
+#[cfg(not(feature = "fixed-point"))]
 use num_traits::float::FloatCore;
 
 /// Converts HSV to RGB8.
 /// h, s, v all in [0,1]
+#[cfg(not(feature = "fixed-point"))]
 fn hsv2rgb(h: f32, s: f32, v: f32) -> RGB8 {
     let h = h.fract();
     let s = s.clamp(0.0, 1.0);
     let v = v.clamp(0.0, 1.0);
 
-    let i = (h * 6.0).floor();
+    let i = FloatCore::floor(h * 6.0);
     let f = h * 6.0 - i;
     let p = v * (1.0 - s);
     let q = v * (1.0 - f * s);
@@ -35,20 +42,38 @@ fn hsv2rgb(h: f32, s: f32, v: f32) -> RGB8 {
 }
 
 /// Pixelblaze-like wave function: 0.5 + 0.5 * sin(PI * x)
+#[cfg(not(feature = "fixed-point"))]
 fn wave(x: f32) -> f32 {
     0.5 + 0.5 * sinf(PI * x)
 }
 
+#[cfg(feature = "fixed-point")]
+const CENTER_OFFSET: Q16 = Q16::from_f32(0.1);
+
+/// Fixed-point `wave`, 0.0..=1.0 scaled to 0..=255.
+#[cfg(feature = "fixed-point")]
+fn wave8(x: Q16) -> u8 {
+    sin8(x.half_turns8())
+}
+
 pub struct DrogenEffect {
+    pub speed: f32,
     t1: f32,
+    #[cfg(feature = "fixed-point")]
+    t1_fixed: Q16,
     num_leds: usize,
+    palette: Option<&'static Palette>,
 }
 
 impl DrogenEffect {
     pub fn new(num_leds: usize) -> Self {
         Self {
+            speed: 0.4,
             t1: 0.0,
+            #[cfg(feature = "fixed-point")]
+            t1_fixed: Q16::ZERO,
             num_leds,
+            palette: None,
         }
     }
 }
@@ -57,22 +82,68 @@ impl LedEffect for DrogenEffect {
     fn before_render(&mut self, delta: f32) {
         // Pixelblaze time(0.2) increments at 0.2 Hz (period = 5s)
         // So t1 += delta * 0.2, wrap at 1.0
-        self.t1 += delta * 0.4;
+        self.t1 += delta * self.speed;
         if self.t1 > 1.0 {
             self.t1 -= 1.0;
         }
+        #[cfg(feature = "fixed-point")]
+        {
+            self.t1_fixed = Q16::from_f32(self.t1);
+        }
     }
 
+    #[cfg(not(feature = "fixed-point"))]
     fn render(&self, index: usize, _num_leds: usize) -> RGB8 {
         let hl = self.num_leds as f32 / 2.0;
         let i = index as f32;
         let mut c = 0.1 - fabsf(i - hl) / hl;
         c = wave(c);
         c = wave(c + self.t1);
-        hsv2rgb(c, 1.0, 1.0)
+        match self.palette {
+            Some(palette) => palette.color_at(c),
+            None => hsv2rgb(c, 1.0, 1.0),
+        }
+    }
+
+    #[cfg(feature = "fixed-point")]
+    fn render(&self, index: usize, _num_leds: usize) -> RGB8 {
+        // |i - hl| / hl with hl = num_leds / 2
+        let distance = Q16::from_ratio(
+            (2 * index as i32 - self.num_leds as i32).abs(),
+            self.num_leds as i32,
+        );
+        let c = wave8(CENTER_OFFSET - distance);
+        let c = wave8(Q16::from_u8(c) + self.t1_fixed);
+        match self.palette {
+            Some(palette) => palette.color_at8(c),
+            None => hsv8(c, 255, 255),
+        }
     }
 
     fn name(&self) -> &str {
         "Drogen"
     }
+
+    fn parameters(&self) -> &'static [&'static str] {
+        &["speed"]
+    }
+
+    fn parameter(&self, name: &str) -> Option<f32> {
+        match name {
+            "speed" => Some(self.speed),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "speed" => self.speed = value.max(0.0),
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_palette(&mut self, palette: Option<&'static Palette>) {
+        self.palette = palette;
+    }
 }
\ No newline at end of file
@@ -0,0 +1,169 @@
+use smart_leds::RGB8;
+
+use crate::dmx::DmxMapping;
+
+/// UDP port E1.31 (sACN) data is sent to.
+pub const E131_PORT: u16 = 5568;
+
+const ACN_PACKET_IDENTIFIER: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const VECTOR_ROOT_E131_DATA: u32 = 0x0000_0004;
+const VECTOR_E131_DATA_PACKET: u32 = 0x0000_0002;
+const VECTOR_DMP_SET_PROPERTY: u8 = 0x02;
+const DMP_ADDRESS_AND_DATA_TYPE: u8 = 0xA1;
+
+const OPTION_PREVIEW_DATA: u8 = 0x80;
+const OPTION_STREAM_TERMINATED: u8 = 0x40;
+
+// Offsets of the fields used here, see ANSI E1.31 section 4.
+const ROOT_VECTOR: usize = 18;
+const FRAMING_VECTOR: usize = 40;
+const PRIORITY: usize = 108;
+const SEQUENCE_NUMBER: usize = 111;
+const OPTIONS: usize = 112;
+const UNIVERSE: usize = 113;
+const DMP_VECTOR: usize = 117;
+const DMP_ADDRESS_TYPE: usize = 118;
+const PROPERTY_VALUE_COUNT: usize = 123;
+const START_CODE: usize = 125;
+const HEADER_SIZE: usize = 126;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum E131Error {
+    /// Shorter than the E1.31 headers or than the property count claims.
+    Truncated,
+    /// Not an ACN packet or not carrying E1.31 data.
+    NotE131,
+}
+
+/// A parsed E1.31 data packet, borrowing the channel values from the
+/// received datagram.
+#[derive(Debug, Clone, Copy)]
+pub struct E131Packet<'a> {
+    pub universe: u16,
+    pub priority: u8,
+    pub sequence: u8,
+    pub options: u8,
+    pub start_code: u8,
+    pub channels: &'a [u8],
+}
+
+impl<'a> E131Packet<'a> {
+    /// Parses the root, framing and DMP layers of an E1.31 data packet.
+    pub fn parse(data: &'a [u8]) -> Result<Self, E131Error> {
+        if data.len() < HEADER_SIZE {
+            return Err(E131Error::Truncated);
+        }
+        if &data[4..16] != ACN_PACKET_IDENTIFIER
+            || read_u32(data, ROOT_VECTOR) != VECTOR_ROOT_E131_DATA
+            || read_u32(data, FRAMING_VECTOR) != VECTOR_E131_DATA_PACKET
+            || data[DMP_VECTOR] != VECTOR_DMP_SET_PROPERTY
+            || data[DMP_ADDRESS_TYPE] != DMP_ADDRESS_AND_DATA_TYPE
+        {
+            return Err(E131Error::NotE131);
+        }
+
+        // The property value count includes the start code.
+        let count = read_u16(data, PROPERTY_VALUE_COUNT) as usize;
+        if count == 0 {
+            return Err(E131Error::Truncated);
+        }
+        let channels = data
+            .get(HEADER_SIZE..START_CODE + count)
+            .ok_or(E131Error::Truncated)?;
+
+        Ok(Self {
+            universe: read_u16(data, UNIVERSE),
+            priority: data[PRIORITY],
+            sequence: data[SEQUENCE_NUMBER],
+            options: data[OPTIONS],
+            start_code: data[START_CODE],
+            channels,
+        })
+    }
+
+    /// True if the source announced that it stopped sending this universe.
+    pub fn is_stream_terminated(&self) -> bool {
+        self.options & OPTION_STREAM_TERMINATED != 0
+    }
+
+    /// True for visualiser-only data that should not drive real fixtures.
+    pub fn is_preview(&self) -> bool {
+        self.options & OPTION_PREVIEW_DATA != 0
+    }
+}
+
+/// Multicast group a universe is sent to (239.255.<universe hi>.<universe lo>).
+pub fn multicast_address(universe: u16) -> [u8; 4] {
+    let [high, low] = universe.to_be_bytes();
+    [239, 255, high, low]
+}
+
+/// Picks the packets meant for the strip out of the E1.31 traffic and maps
+/// their channels onto LEDs.
+pub struct E131Receiver {
+    pub universe: u16,
+    pub mapping: DmxMapping,
+    last_sequence: Option<u8>,
+}
+
+/// What the firmware should do with a received packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum E131Action {
+    /// Not for us (other universe, preview data, stale or non-dimmer).
+    Ignore,
+    /// New channel values, show them with `pixel()`.
+    Frame,
+    /// The source stopped streaming, fall back to local effects.
+    Stop,
+}
+
+impl E131Receiver {
+    pub fn new(universe: u16, start_channel: usize) -> Self {
+        Self {
+            universe,
+            mapping: DmxMapping::new(start_channel),
+            last_sequence: None,
+        }
+    }
+
+    pub fn accept(&mut self, packet: &E131Packet) -> E131Action {
+        if packet.universe != self.universe || packet.is_preview() {
+            return E131Action::Ignore;
+        }
+        if packet.is_stream_terminated() {
+            self.last_sequence = None;
+            return E131Action::Stop;
+        }
+        // E1.31 6.7.2: drop packets up to 20 behind the last one, they
+        // arrived out of order. Anything further back is a restarted source.
+        if let Some(last) = self.last_sequence {
+            let diff = packet.sequence.wrapping_sub(last) as i8;
+            if diff <= 0 && diff > -20 {
+                return E131Action::Ignore;
+            }
+        }
+        self.last_sequence = Some(packet.sequence);
+        if packet.start_code != 0 {
+            return E131Action::Ignore;
+        }
+        E131Action::Frame
+    }
+
+    /// Returns the colour of LED `index` in an accepted packet.
+    pub fn pixel(&self, packet: &E131Packet, index: usize) -> RGB8 {
+        self.mapping.pixel(packet.channels, index)
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
@@ -2,6 +2,9 @@ use smart_leds::{
     RGB8
 };
 
+use crate::output::{RGB16, RGBW8};
+use crate::palette::Palette;
+
 pub trait LedEffect {
     /// Called once per frame to update time-based animation logic.
     /// `delta` is the time in milliseconds since the last frame.
@@ -12,7 +15,45 @@ pub trait LedEffect {
     /// `num_leds` is the total number of LEDs in the strip.
     fn render(&self, index: usize, num_leds: usize) -> RGB8;
 
+    /// Like `render` at 16 bit, 8.8 fixed point so 256 is one 8 bit step.
+    /// Effects with fades finer than 8 bit override it so the controller can
+    /// dither them, the default widens `render`.
+    fn render16(&self, index: usize, num_leds: usize) -> RGB16 {
+        let color = self.render(index, num_leds);
+        RGB16 {
+            r: (color.r as u16) << 8,
+            g: (color.g as u16) << 8,
+            b: (color.b as u16) << 8,
+        }
+    }
+
+    /// Like `render`, for effects that drive the white channel of RGBW
+    /// strips themselves. `None` lets the output extract white from `render`.
+    fn render_rgbw(&self, _index: usize, _num_leds: usize) -> Option<RGBW8> {
+        None
+    }
+
     /// Returns the name of the effect.
     fn name(&self) -> &str;
+
+    /// Names of the parameters that can be tuned at runtime.
+    fn parameters(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Returns the current value of a parameter, `None` if the effect has no
+    /// parameter of that name.
+    fn parameter(&self, _name: &str) -> Option<f32> {
+        None
+    }
+
+    /// Sets a parameter, returns false if the effect has no parameter of that name.
+    fn set_parameter(&mut self, _name: &str, _value: f32) -> bool {
+        false
+    }
+
+    /// Selects the palette to draw colours from, `None` restores the
+    /// effect's own colours. Effects that have no use for a palette ignore it.
+    fn set_palette(&mut self, _palette: Option<&'static Palette>) {}
 }
 
@@ -0,0 +1,188 @@
+//! Fixed-point math for chips without an FPU, in the spirit of FastLED's
+//! `lib8tion`: a Q16.16 number type and 8 bit scaled helpers.
+//!
+//! Angles are `u8` with 256 steps per turn, fractions are `u8` where 255 is
+//! (almost) 1.0.
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+use smart_leds::RGB8;
+
+/// Signed Q16.16 fixed-point number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Q16(pub i32);
+
+impl Q16 {
+    pub const ZERO: Q16 = Q16(0);
+    pub const ONE: Q16 = Q16(1 << 16);
+
+    pub const fn from_int(value: i32) -> Self {
+        Q16(value << 16)
+    }
+
+    pub const fn from_f32(value: f32) -> Self {
+        Q16((value * 65536.0) as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / 65536.0
+    }
+
+    /// `numerator / denominator` without going through a float.
+    pub const fn from_ratio(numerator: i32, denominator: i32) -> Self {
+        Q16((((numerator as i64) << 16) / denominator as i64) as i32)
+    }
+
+    /// A `u8` fraction, 255 maps to 1.0.
+    pub const fn from_u8(fraction: u8) -> Self {
+        // 65536 / 255 is 257 plus a rounding error of 1/255.
+        Q16(fraction as i32 * 257)
+    }
+
+    pub const fn abs(self) -> Self {
+        Q16(self.0.abs())
+    }
+
+    /// Largest integer not greater than the number.
+    pub const fn floor(self) -> i32 {
+        self.0 >> 16
+    }
+
+    /// Fractional part, always in `[0, 1)`.
+    pub const fn fract(self) -> Self {
+        Q16(self.0 & 0xFFFF)
+    }
+
+    /// The number as an angle where 2.0 is one turn, matching `sin(PI * x)`.
+    pub const fn half_turns8(self) -> u8 {
+        (self.0 >> 9) as u8
+    }
+}
+
+impl Add for Q16 {
+    type Output = Q16;
+
+    fn add(self, other: Q16) -> Q16 {
+        Q16(self.0.wrapping_add(other.0))
+    }
+}
+
+impl Sub for Q16 {
+    type Output = Q16;
+
+    fn sub(self, other: Q16) -> Q16 {
+        Q16(self.0.wrapping_sub(other.0))
+    }
+}
+
+impl Mul for Q16 {
+    type Output = Q16;
+
+    fn mul(self, other: Q16) -> Q16 {
+        Q16(((self.0 as i64 * other.0 as i64) >> 16) as i32)
+    }
+}
+
+impl Neg for Q16 {
+    type Output = Q16;
+
+    fn neg(self) -> Q16 {
+        Q16(self.0.wrapping_neg())
+    }
+}
+
+/// Scales `value` by `scale / 256`, a scale of 255 keeps 255 at 255.
+pub const fn scale8(value: u8, scale: u8) -> u8 {
+    ((value as u16 * (1 + scale as u16)) >> 8) as u8
+}
+
+/// Scales `value` by `scale / 65536`.
+pub const fn scale16(value: u16, scale: u16) -> u16 {
+    ((value as u32 * (1 + scale as u32)) >> 16) as u16
+}
+
+/// Adds, saturating at 255.
+pub const fn qadd8(a: u8, b: u8) -> u8 {
+    a.saturating_add(b)
+}
+
+/// Subtracts, saturating at 0.
+pub const fn qsub8(a: u8, b: u8) -> u8 {
+    a.saturating_sub(b)
+}
+
+/// Blends from `a` (fraction 0) towards `b` (fraction 255).
+pub const fn lerp8(a: u8, b: u8, fraction: u8) -> u8 {
+    if b > a {
+        a + scale8(b - a, fraction)
+    } else {
+        a - scale8(a - b, fraction)
+    }
+}
+
+/// Slopes and offsets of the four line segments approximating a quarter sine.
+const SIN8_SEGMENTS: [u8; 8] = [0, 49, 49, 41, 90, 27, 117, 10];
+
+/// Sine of `theta` scaled to `0..=255`, 128 being zero. Piecewise linear,
+/// within 3.5 steps, under 1.4% of the swing, of `sinf`.
+pub const fn sin8(theta: u8) -> u8 {
+    let mut offset = theta;
+    if theta & 0x40 != 0 {
+        offset = 255 - offset;
+    }
+    offset &= 0x3F;
+
+    let mut segment_offset = offset & 0x0F;
+    if theta & 0x40 != 0 {
+        segment_offset += 1;
+    }
+
+    let segment = (offset >> 4) as usize;
+    let base = SIN8_SEGMENTS[segment * 2] as i16;
+    let slope = SIN8_SEGMENTS[segment * 2 + 1] as i16;
+    let mut y = ((slope * segment_offset as i16) >> 4) + base;
+    if theta & 0x80 != 0 {
+        y = -y;
+    }
+    (y + 128) as u8
+}
+
+/// Cosine of `theta` scaled like `sin8`.
+pub const fn cos8(theta: u8) -> u8 {
+    sin8(theta.wrapping_add(64))
+}
+
+/// Sawtooth rising from 0 to 65535 `bpm` times a minute.
+pub const fn beat16(bpm: u8, millis: u32) -> u16 {
+    (millis as u64 * bpm as u64 * 65536 / 60_000) as u16
+}
+
+/// Sawtooth rising from 0 to 255 `bpm` times a minute.
+pub const fn beat8(bpm: u8, millis: u32) -> u8 {
+    (beat16(bpm, millis) >> 8) as u8
+}
+
+/// Sine wave between `low` and `high` at `bpm` beats a minute.
+pub const fn beatsin8(bpm: u8, low: u8, high: u8, millis: u32) -> u8 {
+    low + scale8(sin8(beat8(bpm, millis)), high - low)
+}
+
+/// HSV to RGB with the hue circle mapped onto `0..=255`.
+pub const fn hsv8(hue: u8, saturation: u8, value: u8) -> RGB8 {
+    // Six sectors of 43 hues each, `remainder` is the position in the sector.
+    let sector = hue / 43;
+    let remainder = (hue - sector * 43) * 6;
+
+    let p = scale8(value, 255 - saturation);
+    let q = scale8(value, 255 - scale8(saturation, remainder));
+    let t = scale8(value, 255 - scale8(saturation, 255 - remainder));
+
+    match sector {
+        0 => RGB8::new(value, t, p),
+        1 => RGB8::new(q, value, p),
+        2 => RGB8::new(p, value, t),
+        3 => RGB8::new(p, q, value),
+        4 => RGB8::new(t, p, value),
+        _ => RGB8::new(value, p, q),
+    }
+}
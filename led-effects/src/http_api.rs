@@ -0,0 +1,362 @@
+//! HTTP/JSON control API for the `EffectController`.
+//!
+//! Only request parsing and routing live here so the API can be exercised on
+//! the host; the firmware owns the sockets and feeds the received bytes in.
+//!
+//! | route                     | body                               |
+//! |---------------------------|------------------------------------|
+//! | `GET /effects`            |                                    |
+//! | `GET /state`              |                                    |
+//! | `POST /effect`            | `{"name":"Rocket"}`                |
+//! | `POST /brightness`        | `{"brightness":128}`               |
+//! | `GET /effect/parameters`  |                                    |
+//! | `POST /effect/parameters` | `{"name":"speed","value":1.5}`     |
+//!
+//! POST routes answer with the new state, errors with `{"error":"..."}`.
+//!
+//! The WLED JSON API subset is served under `/json`, see the `wled` module.
+
+use core::fmt::Write;
+
+use heapless::Vec;
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::controller::{ControllerError, EffectController, MAX_EFFECTS};
+use crate::effect::LedEffect;
+use crate::wled::{self, DeviceInfo, WledError};
+
+/// Space reserved in front of the body for the status line and headers.
+const HEAD_SPACE: usize = 128;
+
+/// Largest request, headers and body, the server reads.
+pub const MAX_REQUEST: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The headers or the body have not been received completely yet.
+    Incomplete,
+    /// Not an HTTP request this server understands.
+    Malformed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    BadRequest,
+    NotFound,
+    MethodNotAllowed,
+    InternalServerError,
+}
+
+impl Status {
+    pub fn code(self) -> u16 {
+        match self {
+            Status::Ok => 200,
+            Status::BadRequest => 400,
+            Status::NotFound => 404,
+            Status::MethodNotAllowed => 405,
+            Status::InternalServerError => 500,
+        }
+    }
+
+    pub fn reason(self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::BadRequest => "Bad Request",
+            Status::NotFound => "Not Found",
+            Status::MethodNotAllowed => "Method Not Allowed",
+            Status::InternalServerError => "Internal Server Error",
+        }
+    }
+}
+
+pub struct Request<'a> {
+    pub method: Method,
+    /// Request path without the query string.
+    pub path: &'a str,
+    pub body: &'a [u8],
+}
+
+impl<'a> Request<'a> {
+    /// Parses a request from the bytes received so far. A body that cannot
+    /// fit in `MAX_REQUEST` bytes is malformed.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let head_end = data
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .ok_or(ParseError::Incomplete)?;
+        let head = core::str::from_utf8(&data[..head_end]).map_err(|_| ParseError::Malformed)?;
+
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next().unwrap_or("").split(' ');
+        let method = match request_line.next() {
+            Some("GET") => Method::Get,
+            Some("POST") => Method::Post,
+            Some(_) => Method::Other,
+            None => return Err(ParseError::Malformed),
+        };
+        let target = request_line.next().ok_or(ParseError::Malformed)?;
+        let path = target.split('?').next().unwrap_or(target);
+
+        let mut content_length = 0;
+        for line in lines {
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().map_err(|_| ParseError::Malformed)?;
+                }
+            }
+        }
+
+        let body_start = head_end + 4;
+        let body_end = body_start
+            .checked_add(content_length)
+            .filter(|&end| end <= MAX_REQUEST)
+            .ok_or(ParseError::Malformed)?;
+        let body = data
+            .get(body_start..body_end)
+            .ok_or(ParseError::Incomplete)?;
+
+        Ok(Self { method, path, body })
+    }
+}
+
+struct ApiError {
+    status: Status,
+    message: &'static str,
+}
+
+impl ApiError {
+    fn new(status: Status, message: &'static str) -> Self {
+        Self { status, message }
+    }
+}
+
+impl From<ControllerError> for ApiError {
+    fn from(error: ControllerError) -> Self {
+        match error {
+            ControllerError::UnknownEffect => ApiError::new(Status::NotFound, "unknown effect"),
+            ControllerError::Empty => {
+                ApiError::new(Status::InternalServerError, "no effects registered")
+            }
+            ControllerError::DuplicateName => ApiError::new(Status::BadRequest, "duplicate effect"),
+            ControllerError::Full => ApiError::new(Status::InternalServerError, "too many effects"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SetEffect<'a> {
+    name: &'a str,
+}
+
+#[derive(Deserialize)]
+struct SetBrightness {
+    brightness: u8,
+}
+
+#[derive(Deserialize)]
+struct SetParameter<'a> {
+    name: &'a str,
+    value: f32,
+}
+
+#[derive(Serialize)]
+struct State<'a> {
+    effect: &'a str,
+    brightness: u8,
+    parameters: Parameters<'a>,
+}
+
+#[derive(Serialize)]
+struct Error<'a> {
+    error: &'a str,
+}
+
+/// Serializes the registered effect names as a JSON array.
+pub(crate) struct EffectList(Vec<&'static str, MAX_EFFECTS>);
+
+impl EffectList {
+    pub(crate) fn new<const N: usize>(controller: &EffectController<N>) -> Self {
+        Self(controller.effect_names().collect())
+    }
+}
+
+impl Serialize for EffectList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+/// Serializes the parameters of an effect as a JSON object of name to value.
+struct Parameters<'a>(&'a dyn LedEffect);
+
+impl Serialize for Parameters<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let names = self.0.parameters();
+        let mut map = serializer.serialize_map(Some(names.len()))?;
+        for name in names {
+            map.serialize_entry(name, &self.0.parameter(name).unwrap_or(0.0))?;
+        }
+        map.end()
+    }
+}
+
+/// Handles `request` against the controller and writes the complete HTTP
+/// response into `out`, returning its length.
+///
+/// `out` must be larger than the 128 bytes reserved for the response head.
+pub fn respond<const N: usize>(
+    controller: &mut EffectController<N>,
+    device: &DeviceInfo,
+    request: &Request,
+    out: &mut [u8],
+) -> usize {
+    let (status, body_len) = match route(controller, device, request, &mut out[HEAD_SPACE..]) {
+        Ok(len) => (Status::Ok, len),
+        Err(error) => {
+            let body = Error {
+                error: error.message,
+            };
+            let len = serde_json_core::to_slice(&body, &mut out[HEAD_SPACE..]).unwrap_or(0);
+            (error.status, len)
+        }
+    };
+    finish(status, body_len, out)
+}
+
+/// Writes a bodyless error response into `out`, e.g. for requests that
+/// could not be parsed.
+pub fn respond_error(status: Status, out: &mut [u8]) -> usize {
+    finish(status, 0, out)
+}
+
+fn route<const N: usize>(
+    controller: &mut EffectController<N>,
+    device: &DeviceInfo,
+    request: &Request,
+    body: &mut [u8],
+) -> Result<usize, ApiError> {
+    match (request.method, request.path) {
+        (Method::Get, "/effects") => to_json(&EffectList::new(controller), body),
+        (Method::Get, "/state") => state(controller, body),
+        (Method::Post, "/effect") => {
+            let SetEffect { name } = from_json(request.body)?;
+            controller.set_effect_by_name(name)?;
+            state(controller, body)
+        }
+        (Method::Post, "/brightness") => {
+            let SetBrightness { brightness } = from_json(request.body)?;
+            controller.set_brightness(brightness);
+            state(controller, body)
+        }
+        (Method::Get, "/effect/parameters") => {
+            to_json(&Parameters(controller.current_effect()?), body)
+        }
+        (Method::Post, "/effect/parameters") => {
+            let SetParameter { name, value } = from_json(request.body)?;
+            if !controller.get_current_effect()?.set_parameter(name, value) {
+                return Err(ApiError::new(Status::NotFound, "unknown parameter"));
+            }
+            to_json(&Parameters(controller.current_effect()?), body)
+        }
+        (Method::Get, "/json") => json(wled::full(controller, device, body)),
+        (Method::Get, "/json/state") => json(wled::state(controller, device, body)),
+        (Method::Get, "/json/info") => json(wled::info(controller, device, body)),
+        (Method::Get, "/json/effects" | "/json/eff") => {
+            to_json(&EffectList::new(controller), body)
+        }
+        (Method::Get, "/json/palettes" | "/json/pal") => json(wled::palettes(body)),
+        (Method::Post, "/json" | "/json/state") => {
+            let verbose = wled::apply_state(controller, request.body).map_err(|e| match e {
+                WledError::InvalidJson => ApiError::new(Status::BadRequest, "invalid json"),
+                WledError::UnknownEffect => ApiError::new(Status::NotFound, "unknown effect"),
+                WledError::UnknownPalette => ApiError::new(Status::NotFound, "unknown palette"),
+            })?;
+            if verbose {
+                json(wled::state(controller, device, body))
+            } else {
+                json(wled::success(body))
+            }
+        }
+        (
+            _,
+            "/effects" | "/state" | "/effect" | "/brightness" | "/effect/parameters" | "/json"
+            | "/json/state" | "/json/info" | "/json/effects" | "/json/eff" | "/json/palettes"
+            | "/json/pal",
+        ) => Err(ApiError::new(Status::MethodNotAllowed, "method not allowed")),
+        _ => Err(ApiError::new(Status::NotFound, "not found")),
+    }
+}
+
+fn state<const N: usize>(
+    controller: &EffectController<N>,
+    body: &mut [u8],
+) -> Result<usize, ApiError> {
+    let effect = controller.current_effect()?;
+    to_json(
+        &State {
+            effect: effect.name(),
+            brightness: controller.brightness(),
+            parameters: Parameters(effect),
+        },
+        body,
+    )
+}
+
+fn from_json<'a, T: Deserialize<'a>>(body: &'a [u8]) -> Result<T, ApiError> {
+    serde_json_core::from_slice(body)
+        .map(|(value, _)| value)
+        .map_err(|_| ApiError::new(Status::BadRequest, "invalid json"))
+}
+
+fn to_json<T: Serialize>(value: &T, body: &mut [u8]) -> Result<usize, ApiError> {
+    json(serde_json_core::to_slice(value, body))
+}
+
+fn json(written: Result<usize, serde_json_core::ser::Error>) -> Result<usize, ApiError> {
+    written.map_err(|_| ApiError::new(Status::InternalServerError, "response too large"))
+}
+
+/// Writes the status line and headers in front of the body, which was
+/// serialized `HEAD_SPACE` bytes into `out`.
+fn finish(status: Status, body_len: usize, out: &mut [u8]) -> usize {
+    let mut head = SliceWriter {
+        buf: [0; HEAD_SPACE],
+        len: 0,
+    };
+    let _ = write!(
+        head,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status.code(),
+        status.reason(),
+        body_len
+    );
+    out.copy_within(HEAD_SPACE..HEAD_SPACE + body_len, head.len);
+    out[..head.len].copy_from_slice(&head.buf[..head.len]);
+    head.len + body_len
+}
+
+struct SliceWriter {
+    buf: [u8; HEAD_SPACE],
+    len: usize,
+}
+
+impl Write for SliceWriter {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(core::fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
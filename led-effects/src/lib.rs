@@ -1,9 +1,21 @@
 #![no_std]
 
 pub mod effect;
+pub mod fixed;
 pub mod controller;
 pub mod solid_effect;
 pub mod policedot_effect;
 pub mod policetrail_effect;
 pub mod drogen_effect;
-pub mod rocket_effect;
\ No newline at end of file
+pub mod rocket_effect;
+pub mod registry;
+pub mod output;
+pub mod stream;
+pub mod dmx;
+pub mod adalight;
+pub mod tpm2;
+pub mod e131;
+pub mod http_api;
+pub mod mqtt;
+pub mod palette;
+pub mod wled;
@@ -0,0 +1,229 @@
+//! Home Assistant integration over MQTT, using the JSON schema of the MQTT
+//! `light` integration.
+//!
+//! This module only builds topics and payloads and applies commands to the
+//! `EffectController`; the firmware owns the MQTT connection. To watch a
+//! device from a Linux box running mosquitto:
+//!
+//! ```text
+//! mosquitto_sub -v -t 'homeassistant/light/#' -t 'led-effects/#'
+//! mosquitto_pub -t 'led-effects/<node id>/set' -m '{"state":"ON","effect":"Rocket"}'
+//! ```
+//!
+//! `examples/mqtt_light.rs` (`just led-mqtt`) runs the same flow on the host
+//! against such a broker, without a board.
+
+use core::fmt::Write;
+
+use heapless::String;
+use serde::{Deserialize, Serialize};
+use smart_leds::RGB8;
+
+use crate::controller::EffectController;
+use crate::http_api::EffectList;
+
+/// Topic prefix Home Assistant watches for discovery configs.
+pub const DISCOVERY_PREFIX: &str = "homeassistant";
+/// Prefix of the command, state and availability topics.
+pub const TOPIC_PREFIX: &str = "led-effects";
+
+pub const PAYLOAD_ONLINE: &str = "online";
+pub const PAYLOAD_OFFLINE: &str = "offline";
+
+/// MQTT topics of one light, derived from its node id.
+pub struct Topics {
+    pub config: String<64>,
+    pub command: String<64>,
+    pub state: String<64>,
+    pub availability: String<64>,
+}
+
+impl Topics {
+    /// `node_id` identifies the device, e.g. derived from its MAC address.
+    /// It should only contain `[a-zA-Z0-9_-]`.
+    pub fn new(node_id: &str) -> Self {
+        Self {
+            config: topic(format_args!("{DISCOVERY_PREFIX}/light/{node_id}/config")),
+            command: topic(format_args!("{TOPIC_PREFIX}/{node_id}/set")),
+            state: topic(format_args!("{TOPIC_PREFIX}/{node_id}/state")),
+            availability: topic(format_args!("{TOPIC_PREFIX}/{node_id}/availability")),
+        }
+    }
+}
+
+/// Builds a node id unique to the device from its whole MAC address.
+pub fn node_id(mac: [u8; 6]) -> String<24> {
+    let mut id = String::new();
+    write!(id, "{TOPIC_PREFIX}_").expect("node id fits");
+    for byte in mac {
+        // The prefix and twelve hex digits take all 24 characters.
+        write!(id, "{byte:02x}").expect("node id fits");
+    }
+    id
+}
+
+fn topic(args: core::fmt::Arguments) -> String<64> {
+    let mut topic = String::new();
+    // Node ids long enough to overflow are truncated rather than rejected.
+    let _ = topic.write_fmt(args);
+    topic
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandError {
+    /// The payload is not a JSON schema command.
+    InvalidJson,
+    /// The requested effect is not registered.
+    UnknownEffect,
+    /// A colour was sent but no effect takes one.
+    NoColorEffect,
+}
+
+#[derive(Serialize)]
+struct Device<'a> {
+    identifiers: [&'a str; 1],
+    name: &'a str,
+    model: &'a str,
+}
+
+#[derive(Serialize)]
+struct DiscoveryConfig<'a> {
+    name: Option<&'a str>,
+    unique_id: &'a str,
+    schema: &'a str,
+    command_topic: &'a str,
+    state_topic: &'a str,
+    availability_topic: &'a str,
+    brightness: bool,
+    effect: bool,
+    effect_list: EffectList,
+    supported_color_modes: [&'a str; 1],
+    device: Device<'a>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+#[derive(Serialize)]
+struct State<'a> {
+    state: &'a str,
+    brightness: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    effect: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color_mode: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<Color>,
+}
+
+#[derive(Deserialize)]
+struct Command<'a> {
+    state: Option<&'a str>,
+    brightness: Option<u8>,
+    effect: Option<&'a str>,
+    color: Option<Color>,
+}
+
+/// Writes the retained discovery config announcing the light to Home
+/// Assistant, including the list of available effects.
+pub fn discovery_config<const N: usize>(
+    controller: &EffectController<N>,
+    node_id: &str,
+    name: &str,
+    topics: &Topics,
+    out: &mut [u8],
+) -> Result<usize, serde_json_core::ser::Error> {
+    let config = DiscoveryConfig {
+        // The entity takes the device name.
+        name: None,
+        unique_id: node_id,
+        schema: "json",
+        command_topic: &topics.command,
+        state_topic: &topics.state,
+        availability_topic: &topics.availability,
+        brightness: true,
+        effect: true,
+        effect_list: EffectList::new(controller),
+        supported_color_modes: ["rgb"],
+        device: Device {
+            identifiers: [node_id],
+            name,
+            model: "led-effects",
+        },
+    };
+    serde_json_core::to_slice(&config, out)
+}
+
+/// Writes the current state of the controller as a JSON schema state message.
+pub fn state<const N: usize>(controller: &EffectController<N>, out: &mut [u8]) -> Result<usize, serde_json_core::ser::Error> {
+    let color = controller.color().map(|RGB8 { r, g, b }| Color { r, g, b });
+    let state = State {
+        state: if controller.is_on() { "ON" } else { "OFF" },
+        brightness: controller.brightness(),
+        effect: controller.current_effect_name().ok(),
+        color_mode: color.map(|_| "rgb"),
+        color,
+    };
+    serde_json_core::to_slice(&state, out)
+}
+
+/// Applies a JSON schema command received on the command topic.
+///
+/// The command is checked before anything is changed: an unknown effect or
+/// state, or a colour no effect takes, leaves the light untouched. A colour
+/// sent with an effect must be one that effect takes, a colour on its own
+/// switches to the first effect taking one if the current one does not.
+pub fn apply_command<const N: usize>(controller: &mut EffectController<N>, payload: &[u8]) -> Result<(), CommandError> {
+    let (command, _): (Command, _) =
+        serde_json_core::from_slice(payload).map_err(|_| CommandError::InvalidJson)?;
+
+    let on = match command.state {
+        Some("ON") => Some(true),
+        Some("OFF") => Some(false),
+        Some(_) => return Err(CommandError::InvalidJson),
+        None => None,
+    };
+    let effect = match command.effect {
+        Some(name) => Some(
+            controller
+                .index_of(name)
+                .map_err(|_| CommandError::UnknownEffect)?,
+        ),
+        None => None,
+    };
+    if command.color.is_some() {
+        let takes_color = |index| {
+            controller
+                .effects()
+                .nth(index)
+                .is_some_and(|factory| factory.has_parameter("red"))
+        };
+        let colorable = match effect {
+            Some(index) => takes_color(index),
+            None => controller.color().is_some() || (0..controller.len()).any(takes_color),
+        };
+        if !colorable {
+            return Err(CommandError::NoColorEffect);
+        }
+    }
+
+    if let Some(index) = effect {
+        controller
+            .set_effect_by_index(index)
+            .map_err(|_| CommandError::UnknownEffect)?;
+    }
+    if let Some(Color { r, g, b }) = command.color {
+        controller.set_color(RGB8::new(r, g, b));
+    }
+    if let Some(brightness) = command.brightness {
+        controller.set_brightness(brightness);
+    }
+    if let Some(on) = on {
+        controller.set_on(on);
+    }
+    Ok(())
+}
@@ -0,0 +1,362 @@
+//! LED chipsets and how frames are encoded for them.
+//!
+//! Clockless chipsets (WS2812, WS2811, SK6812) are driven from RMT pulse
+//! codes, APA102 and SK9822 take a byte stream over SPI. The encoding uses no
+//! HAL types so it can be checked on the host.
+//!
+//! Frames can be `RGB8` or `RGBW8`. RGB frames sent to an RGBW chipset get
+//! their white channel extracted as set by the output's `WhiteMode`, RGBW
+//! frames sent to an RGB chipset have the white mixed back in.
+
+use smart_leds::{White, RGB, RGB8, RGBW};
+
+/// Order in which a chipset expects the colour channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorOrder {
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+}
+
+impl ColorOrder {
+    /// The channels of `color` in wire order.
+    pub const fn arrange(self, color: RGB8) -> [u8; 3] {
+        let RGB8 { r, g, b } = color;
+        match self {
+            ColorOrder::Rgb => [r, g, b],
+            ColorOrder::Rbg => [r, b, g],
+            ColorOrder::Grb => [g, r, b],
+            ColorOrder::Gbr => [g, b, r],
+            ColorOrder::Brg => [b, r, g],
+            ColorOrder::Bgr => [b, g, r],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chipset {
+    /// WS2812(B) and compatibles at 800 kHz.
+    Ws2812,
+    /// WS2811 in its 400 kHz mode.
+    Ws2811,
+    /// SK6812 RGB at 800 kHz.
+    Sk6812,
+    /// SK6812 RGBW, the white channel follows the colour channels.
+    Sk6812Rgbw,
+    /// APA102 over SPI.
+    Apa102,
+    /// SK9822 over SPI, an APA102 variant that needs a reset frame.
+    Sk9822,
+}
+
+/// High and low times of the bits of a clockless chipset, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub t0h: u32,
+    pub t0l: u32,
+    pub t1h: u32,
+    pub t1l: u32,
+}
+
+impl Chipset {
+    /// Bit timing of clockless chipsets, `None` for SPI chipsets.
+    pub const fn timing(self) -> Option<Timing> {
+        match self {
+            Chipset::Ws2812 => Some(Timing {
+                t0h: 400,
+                t0l: 850,
+                t1h: 800,
+                t1l: 450,
+            }),
+            Chipset::Ws2811 => Some(Timing {
+                t0h: 500,
+                t0l: 2000,
+                t1h: 1200,
+                t1l: 1300,
+            }),
+            Chipset::Sk6812 | Chipset::Sk6812Rgbw => Some(Timing {
+                t0h: 300,
+                t0l: 900,
+                t1h: 600,
+                t1l: 600,
+            }),
+            Chipset::Apa102 | Chipset::Sk9822 => None,
+        }
+    }
+
+    pub const fn is_rgbw(self) -> bool {
+        matches!(self, Chipset::Sk6812Rgbw)
+    }
+
+    /// Bytes per LED of the colour data.
+    pub const fn channels(self) -> usize {
+        if self.is_rgbw() {
+            4
+        } else {
+            3
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputError {
+    /// The output buffer cannot hold the encoded frame.
+    BufferTooSmall,
+    /// The chipset is not driven this way, e.g. an SPI chipset on the RMT.
+    WrongInterface,
+}
+
+/// A colour with 16 bits per channel, for frames scaled beyond 8 bit precision.
+pub type RGB16 = RGB<u16>;
+
+/// An RGBW colour with 8 bits per channel, the white channel is `a`.
+pub type RGBW8 = RGBW<u8>;
+
+/// How the white channel of an RGBW chipset is derived from an RGB colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhiteMode {
+    /// Moves the part all channels share to white, as if the white LED were
+    /// pure white.
+    MinChannel,
+    /// The white LED has this colour at full brightness, as it would be
+    /// mixed from the RGB LEDs. Keeps the hue of warm and cool white LEDs.
+    Temperature(RGB8),
+}
+
+impl WhiteMode {
+    /// White LED of about 2700 K.
+    pub const WARM_WHITE: WhiteMode = WhiteMode::Temperature(RGB8::new(255, 169, 87));
+    /// White LED of about 4000 K.
+    pub const NEUTRAL_WHITE: WhiteMode = WhiteMode::Temperature(RGB8::new(255, 206, 166));
+    /// White LED of about 6500 K.
+    pub const COOL_WHITE: WhiteMode = WhiteMode::Temperature(RGB8::new(255, 254, 250));
+
+    /// Splits `color` into the colour channels and the white channel.
+    pub const fn extract(self, color: RGB8) -> RGBW8 {
+        let tint = match self {
+            WhiteMode::MinChannel => return extract_white(color),
+            WhiteMode::Temperature(tint) => tint,
+        };
+        // The most white that fits under every channel it lights up.
+        let w = white_share(color.r, tint.r);
+        let w = min(w, white_share(color.g, tint.g));
+        let w = min(w, white_share(color.b, tint.b));
+        RGBW {
+            r: color.r - tint_level(tint.r, w),
+            g: color.g - tint_level(tint.g, w),
+            b: color.b - tint_level(tint.b, w),
+            a: White(w),
+        }
+    }
+
+    /// Mixes the white channel of `color` back into RGB, the inverse of
+    /// `extract` up to rounding.
+    pub const fn combine(self, color: RGBW8) -> RGB8 {
+        let tint = match self {
+            WhiteMode::MinChannel => RGB8::new(255, 255, 255),
+            WhiteMode::Temperature(tint) => tint,
+        };
+        let w = color.a.0;
+        RGB8::new(
+            color.r.saturating_add(tint_level(tint.r, w)),
+            color.g.saturating_add(tint_level(tint.g, w)),
+            color.b.saturating_add(tint_level(tint.b, w)),
+        )
+    }
+}
+
+const fn min(a: u8, b: u8) -> u8 {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+/// White level at which a white LED channel of brightness `tint` reaches `value`.
+const fn white_share(value: u8, tint: u8) -> u8 {
+    if tint == 0 {
+        return 255;
+    }
+    let share = value as u16 * 255 / tint as u16;
+    if share > 255 {
+        255
+    } else {
+        share as u8
+    }
+}
+
+/// Brightness of a white LED channel of brightness `tint` at white level `w`.
+const fn tint_level(tint: u8, w: u8) -> u8 {
+    (tint as u16 * w as u16 / 255) as u8
+}
+
+/// Splits `color` into RGBW by moving the part all channels share to white.
+pub const fn extract_white(color: RGB8) -> RGBW8 {
+    let RGB8 { r, g, b } = color;
+    let w = min(min(r, g), b);
+    RGBW {
+        r: r - w,
+        g: g - w,
+        b: b - w,
+        a: White(w),
+    }
+}
+
+/// RMT pulse code: `high` ticks high, then `low` ticks low, laid out as in
+/// the RMT RAM of the ESP32 family. 0 marks the end of a transmission.
+pub const fn pulse_code(high: u16, low: u16) -> u32 {
+    (1 << 15) | (high as u32 & 0x7FFF) | ((low as u32 & 0x7FFF) << 16)
+}
+
+/// A colour that can be sent to any chipset, `RGB8` or `RGBW8`.
+pub trait Pixel: Copy {
+    fn to_rgb(self, white: WhiteMode) -> RGB8;
+    fn to_rgbw(self, white: WhiteMode) -> RGBW8;
+}
+
+impl Pixel for RGB8 {
+    fn to_rgb(self, _white: WhiteMode) -> RGB8 {
+        self
+    }
+
+    fn to_rgbw(self, white: WhiteMode) -> RGBW8 {
+        white.extract(self)
+    }
+}
+
+impl Pixel for RGBW8 {
+    fn to_rgb(self, white: WhiteMode) -> RGB8 {
+        white.combine(self)
+    }
+
+    fn to_rgbw(self, _white: WhiteMode) -> RGBW8 {
+        self
+    }
+}
+
+/// Chipset, colour order and white handling of one output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputConfig {
+    pub chipset: Chipset,
+    pub color_order: ColorOrder,
+    /// Only used when RGB is sent to an RGBW chipset or the other way round.
+    pub white: WhiteMode,
+}
+
+impl OutputConfig {
+    /// An output with `WhiteMode::MinChannel`.
+    pub const fn new(chipset: Chipset, color_order: ColorOrder) -> Self {
+        Self {
+            chipset,
+            color_order,
+            white: WhiteMode::MinChannel,
+        }
+    }
+
+    pub const fn with_white(self, white: WhiteMode) -> Self {
+        Self { white, ..self }
+    }
+
+    /// Pulse codes needed for `num_leds`, including the end marker.
+    pub const fn rmt_len(self, num_leds: usize) -> usize {
+        num_leds * self.chipset.channels() * 8 + 1
+    }
+
+    /// Bytes of an SPI frame for `num_leds`.
+    pub const fn spi_len(self, num_leds: usize) -> usize {
+        4 + num_leds * 4 + self.spi_end_len(num_leds)
+    }
+
+    /// The end frame has to clock out one bit per two LEDs to push the data
+    /// through the chain; SK9822 also needs a 32 bit reset frame first.
+    const fn spi_end_len(self, num_leds: usize) -> usize {
+        let push = num_leds.div_ceil(16);
+        match self.chipset {
+            Chipset::Sk9822 => 4 + push,
+            _ => {
+                if push < 4 {
+                    4
+                } else {
+                    push
+                }
+            }
+        }
+    }
+
+    /// Colour bytes of one LED in wire order, the length is `channels()`.
+    fn pixel(self, color: impl Pixel) -> ([u8; 4], usize) {
+        if self.chipset.is_rgbw() {
+            let rgbw = color.to_rgbw(self.white);
+            let [a, b, c] = self.color_order.arrange(RGB8::new(rgbw.r, rgbw.g, rgbw.b));
+            ([a, b, c, rgbw.a.0], 4)
+        } else {
+            let [a, b, c] = self.color_order.arrange(color.to_rgb(self.white));
+            ([a, b, c, 0], 3)
+        }
+    }
+
+    /// Encodes `pixels` as RMT pulse codes for an RMT clocked at `clock_mhz`
+    /// and returns the number of codes written, end marker included.
+    pub fn encode_rmt(
+        self,
+        pixels: impl IntoIterator<Item = impl Pixel>,
+        clock_mhz: u32,
+        out: &mut [u32],
+    ) -> Result<usize, OutputError> {
+        let timing = self.chipset.timing().ok_or(OutputError::WrongInterface)?;
+        let ticks = |ns: u32| (ns * clock_mhz / 1000) as u16;
+        let zero = pulse_code(ticks(timing.t0h), ticks(timing.t0l));
+        let one = pulse_code(ticks(timing.t1h), ticks(timing.t1l));
+
+        let mut len = 0;
+        for color in pixels {
+            let (bytes, channels) = self.pixel(color);
+            for byte in &bytes[..channels] {
+                let codes = out
+                    .get_mut(len..len + 8)
+                    .ok_or(OutputError::BufferTooSmall)?;
+                for (bit, code) in codes.iter_mut().enumerate() {
+                    *code = if byte & (0x80 >> bit) != 0 { one } else { zero };
+                }
+                len += 8;
+            }
+        }
+        *out.get_mut(len).ok_or(OutputError::BufferTooSmall)? = 0;
+        Ok(len + 1)
+    }
+
+    /// Encodes `pixels` as an APA102/SK9822 SPI frame at full global
+    /// brightness and returns its length.
+    pub fn encode_spi(
+        self,
+        pixels: impl IntoIterator<Item = impl Pixel>,
+        out: &mut [u8],
+    ) -> Result<usize, OutputError> {
+        let end_byte = match self.chipset {
+            Chipset::Apa102 => 0xFF,
+            Chipset::Sk9822 => 0x00,
+            _ => return Err(OutputError::WrongInterface),
+        };
+
+        let mut len = 4;
+        out.get_mut(..len)
+            .ok_or(OutputError::BufferTooSmall)?
+            .fill(0);
+        for color in pixels {
+            let [a, b, c] = self.color_order.arrange(color.to_rgb(self.white));
+            out.get_mut(len..len + 4)
+                .ok_or(OutputError::BufferTooSmall)?
+                .copy_from_slice(&[0xE0 | 31, a, b, c]);
+            len += 4;
+        }
+        let end = self.spi_end_len((len - 4) / 4);
+        out.get_mut(len..len + end)
+            .ok_or(OutputError::BufferTooSmall)?
+            .fill(end_byte);
+        Ok(len + end)
+    }
+}
@@ -0,0 +1,114 @@
+use smart_leds::RGB8;
+
+use crate::fixed::lerp8;
+
+/// A cyclic colour gradient effects can use instead of their own colours.
+pub struct Palette {
+    pub name: &'static str,
+    stops: &'static [RGB8],
+}
+
+impl Palette {
+    /// Returns the colour at `position` along the palette, wrapping at 1.0.
+    pub fn color_at(&self, position: f32) -> RGB8 {
+        if self.stops.is_empty() {
+            return RGB8::default();
+        }
+        let scaled = (position - libm::floorf(position)) * self.stops.len() as f32;
+        let index = (scaled as usize).min(self.stops.len() - 1);
+        let fraction = scaled - index as f32;
+        let from = self.stops[index];
+        let to = self.stops[(index + 1) % self.stops.len()];
+        RGB8::new(
+            lerp(from.r, to.r, fraction),
+            lerp(from.g, to.g, fraction),
+            lerp(from.b, to.b, fraction),
+        )
+    }
+
+    /// Like `color_at` with the palette mapped onto `0..=255`, without floats.
+    pub fn color_at8(&self, position: u8) -> RGB8 {
+        if self.stops.is_empty() {
+            return RGB8::default();
+        }
+        let scaled = position as usize * self.stops.len();
+        let index = scaled >> 8;
+        let fraction = (scaled & 0xFF) as u8;
+        let from = self.stops[index];
+        let to = self.stops[(index + 1) % self.stops.len()];
+        RGB8::new(
+            lerp8(from.r, to.r, fraction),
+            lerp8(from.g, to.g, fraction),
+            lerp8(from.b, to.b, fraction),
+        )
+    }
+}
+
+fn lerp(from: u8, to: u8, fraction: f32) -> u8 {
+    (from as f32 + (to as f32 - from as f32) * fraction) as u8
+}
+
+/// Palettes selectable on the controller, index 0 means "no palette, effects
+/// use their own colours".
+pub const PALETTES: &[Palette] = &[
+    Palette {
+        name: "Default",
+        stops: &[],
+    },
+    Palette {
+        name: "Rainbow",
+        stops: &[
+            RGB8::new(255, 0, 0),
+            RGB8::new(255, 255, 0),
+            RGB8::new(0, 255, 0),
+            RGB8::new(0, 255, 255),
+            RGB8::new(0, 0, 255),
+            RGB8::new(255, 0, 255),
+        ],
+    },
+    Palette {
+        name: "Party",
+        stops: &[
+            RGB8::new(85, 0, 171),
+            RGB8::new(132, 0, 124),
+            RGB8::new(181, 0, 75),
+            RGB8::new(229, 0, 27),
+            RGB8::new(232, 23, 0),
+            RGB8::new(184, 71, 0),
+            RGB8::new(171, 119, 0),
+            RGB8::new(171, 171, 0),
+        ],
+    },
+    Palette {
+        name: "Lava",
+        stops: &[
+            RGB8::new(0, 0, 0),
+            RGB8::new(128, 0, 0),
+            RGB8::new(255, 0, 0),
+            RGB8::new(255, 128, 0),
+            RGB8::new(255, 255, 255),
+            RGB8::new(255, 128, 0),
+            RGB8::new(128, 0, 0),
+        ],
+    },
+    Palette {
+        name: "Ocean",
+        stops: &[
+            RGB8::new(0, 0, 64),
+            RGB8::new(0, 0, 255),
+            RGB8::new(0, 128, 255),
+            RGB8::new(0, 255, 255),
+            RGB8::new(0, 128, 128),
+        ],
+    },
+    Palette {
+        name: "Forest",
+        stops: &[
+            RGB8::new(0, 64, 0),
+            RGB8::new(0, 128, 0),
+            RGB8::new(85, 107, 47),
+            RGB8::new(154, 205, 50),
+            RGB8::new(34, 139, 34),
+        ],
+    },
+];
@@ -16,10 +16,11 @@ pub struct PoliceDot {
 }
 
 impl PoliceDot {
+    /// `size` is limited to `num_leds`.
     pub fn new(speed: f32, size: usize, num_leds: usize) -> Self {
         Self {
             speed,
-            size,
+            size: size.min(num_leds),
             time: 0.0,
             num_leds,
             red_start: 0,
@@ -73,6 +74,27 @@ impl LedEffect for PoliceDot {
     fn name(&self) -> &str {
         "PoliceDot"
     }
+
+    fn parameters(&self) -> &'static [&'static str] {
+        &["speed", "size"]
+    }
+
+    fn parameter(&self, name: &str) -> Option<f32> {
+        match name {
+            "speed" => Some(self.speed),
+            "size" => Some(self.size as f32),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "speed" => self.speed = value.max(0.0),
+            "size" => self.size = (value.max(0.0) as usize).min(self.num_leds),
+            _ => return false,
+        }
+        true
+    }
 }
 
 
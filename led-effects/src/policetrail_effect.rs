@@ -1,5 +1,6 @@
 use smart_leds::RGB8;
 use crate::effect::LedEffect;
+use crate::output::RGB16;
 use libm::floorf;
 
 pub struct PoliceTrail {
@@ -15,11 +16,12 @@ pub struct PoliceTrail {
 }
 
 impl PoliceTrail {
+    /// `size` and `trail_length` are limited to `num_leds`.
     pub fn new(speed: f32, size: usize, trail_length: usize, num_leds: usize) -> Self {
         Self {
             speed,
-            size,
-            trail_length,
+            size: size.min(num_leds),
+            trail_length: trail_length.min(num_leds),
             time: 0.0,
             num_leds,
             red_pos: 0,
@@ -36,16 +38,17 @@ impl PoliceTrail {
         }
     }
 
-    /// Calculate brightness based on distance from head (255 at head, fading behind)
-    fn trail_brightness(distance: usize, trail_length: usize, size: usize) -> u8 {
+    /// Calculate brightness based on distance from head (255.0 at head, fading
+    /// behind), 8.8 fixed point so the fade keeps its steps below 8 bit
+    fn trail_brightness(distance: usize, trail_length: usize, size: usize) -> u16 {
         if distance < size {
             // Full brightness for the dot itself
-            255
+            0xFF00
         } else if distance < size + trail_length {
-            // Fade from 255 to 0 over trail_length
+            // Fade from 255 to 0 over trail_length, in integers as this runs per pixel.
+            // Does not overflow a 32 bit usize below 65794 LEDs.
             let fade_distance = distance - size;
-            let fade_ratio = 1.0 - (fade_distance as f32 / trail_length as f32);
-            (255.0 * fade_ratio) as u8
+            (0xFF00 * (trail_length - fade_distance) / trail_length) as u16
         } else {
             0
         }
@@ -62,7 +65,16 @@ impl LedEffect for PoliceTrail {
         self.blue_pos = (self.red_pos + half) % self.num_leds;
     }
 
-    fn render(&self, index: usize, _num_leds: usize) -> RGB8 {
+    fn render(&self, index: usize, num_leds: usize) -> RGB8 {
+        let color = self.render16(index, num_leds);
+        RGB8 {
+            r: (color.r >> 8) as u8,
+            g: 0,
+            b: (color.b >> 8) as u8,
+        }
+    }
+
+    fn render16(&self, index: usize, _num_leds: usize) -> RGB16 {
         // Calculate distance behind each dot head
         let red_distance = Self::distance_behind(index, self.red_pos, self.num_leds);
         let blue_distance = Self::distance_behind(index, self.blue_pos, self.num_leds);
@@ -71,10 +83,33 @@ impl LedEffect for PoliceTrail {
         let r = Self::trail_brightness(red_distance, self.trail_length, self.size);
         let b = Self::trail_brightness(blue_distance, self.trail_length, self.size);
 
-        RGB8 { r, g: 0, b }
+        RGB16 { r, g: 0, b }
     }
 
     fn name(&self) -> &str {
         "PoliceTrail"
     }
+
+    fn parameters(&self) -> &'static [&'static str] {
+        &["speed", "size", "trail_length"]
+    }
+
+    fn parameter(&self, name: &str) -> Option<f32> {
+        match name {
+            "speed" => Some(self.speed),
+            "size" => Some(self.size as f32),
+            "trail_length" => Some(self.trail_length as f32),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "speed" => self.speed = value.max(0.0),
+            "size" => self.size = (value.max(0.0) as usize).min(self.num_leds),
+            "trail_length" => self.trail_length = (value.max(0.0) as usize).min(self.num_leds),
+            _ => return false,
+        }
+        true
+    }
 }
\ No newline at end of file
@@ -0,0 +1,247 @@
+//! Registry of the built-in effects.
+//!
+//! Effects are not constructed up front: the controller keeps the factories
+//! and only creates the active effect. Effects are stored as an `Effect<N>`
+//! for a strip of `N` LEDs, so no allocator is needed; with the `alloc`
+//! feature a factory can also box an effect of its own.
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use smart_leds::RGB8;
+
+use crate::drogen_effect::DrogenEffect;
+use crate::effect::LedEffect;
+use crate::output::{RGB16, RGBW8};
+use crate::palette::Palette;
+use crate::policedot_effect::PoliceDot;
+use crate::policetrail_effect::PoliceTrail;
+use crate::rocket_effect::RocketEffect;
+use crate::solid_effect::SolidColor;
+
+/// Any effect the controller can run on a strip of `N` LEDs.
+pub enum Effect<const N: usize> {
+    SolidColor(SolidColor),
+    PoliceDot(PoliceDot),
+    PoliceTrail(PoliceTrail),
+    Drogen(DrogenEffect),
+    Rocket(RocketEffect<N>),
+    #[cfg(feature = "alloc")]
+    Boxed(Box<dyn LedEffect + Send>),
+}
+
+impl<const N: usize> Effect<N> {
+    fn inner(&self) -> &dyn LedEffect {
+        match self {
+            Effect::SolidColor(effect) => effect,
+            Effect::PoliceDot(effect) => effect,
+            Effect::PoliceTrail(effect) => effect,
+            Effect::Drogen(effect) => effect,
+            Effect::Rocket(effect) => effect,
+            #[cfg(feature = "alloc")]
+            Effect::Boxed(effect) => effect.as_ref(),
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut dyn LedEffect {
+        match self {
+            Effect::SolidColor(effect) => effect,
+            Effect::PoliceDot(effect) => effect,
+            Effect::PoliceTrail(effect) => effect,
+            Effect::Drogen(effect) => effect,
+            Effect::Rocket(effect) => effect,
+            #[cfg(feature = "alloc")]
+            Effect::Boxed(effect) => effect.as_mut(),
+        }
+    }
+}
+
+impl<const N: usize> LedEffect for Effect<N> {
+    fn before_render(&mut self, delta: f32) {
+        self.inner_mut().before_render(delta)
+    }
+
+    fn render(&self, index: usize, num_leds: usize) -> RGB8 {
+        self.inner().render(index, num_leds)
+    }
+
+    fn render16(&self, index: usize, num_leds: usize) -> RGB16 {
+        self.inner().render16(index, num_leds)
+    }
+
+    fn render_rgbw(&self, index: usize, num_leds: usize) -> Option<RGBW8> {
+        self.inner().render_rgbw(index, num_leds)
+    }
+
+    fn name(&self) -> &str {
+        self.inner().name()
+    }
+
+    fn parameters(&self) -> &'static [&'static str] {
+        self.inner().parameters()
+    }
+
+    fn parameter(&self, name: &str) -> Option<f32> {
+        self.inner().parameter(name)
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        self.inner_mut().set_parameter(name, value)
+    }
+
+    fn set_palette(&mut self, palette: Option<&'static Palette>) {
+        self.inner_mut().set_palette(palette)
+    }
+}
+
+/// Describes an effect and how to construct it.
+pub struct EffectFactory<const N: usize> {
+    /// Same as the `name()` of the effects it creates.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Every parameter of the effect with the value a new effect starts with.
+    pub defaults: &'static [(&'static str, f32)],
+    constructor: fn(&Params) -> Effect<N>,
+}
+
+impl<const N: usize> EffectFactory<N> {
+    pub const fn new(
+        name: &'static str,
+        description: &'static str,
+        defaults: &'static [(&'static str, f32)],
+        constructor: fn(&Params) -> Effect<N>,
+    ) -> Self {
+        Self {
+            name,
+            description,
+            defaults,
+            constructor,
+        }
+    }
+
+    /// Creates the effect, `params` override single defaults.
+    pub fn create(&self, params: &[(&str, f32)]) -> Effect<N> {
+        (self.constructor)(&Params {
+            overrides: params,
+            defaults: self.defaults,
+        })
+    }
+
+    /// Whether the effect has a parameter of that name.
+    pub fn has_parameter(&self, name: &str) -> bool {
+        self.defaults.iter().any(|&(parameter, _)| parameter == name)
+    }
+}
+
+/// Parameter values handed to a constructor: the overrides, falling back to
+/// the factory defaults.
+pub struct Params<'a> {
+    overrides: &'a [(&'a str, f32)],
+    defaults: &'static [(&'static str, f32)],
+}
+
+impl Params<'_> {
+    /// Value of a parameter, 0.0 if it is neither overridden nor has a default.
+    pub fn get(&self, name: &str) -> f32 {
+        self.overrides
+            .iter()
+            .chain(self.defaults.iter())
+            .find(|&&(parameter, _)| parameter == name)
+            .map_or(0.0, |&(_, value)| value)
+    }
+
+    /// Sets every parameter of `effect` through `set_parameter`.
+    pub fn apply(&self, effect: &mut dyn LedEffect) {
+        for name in effect.parameters() {
+            effect.set_parameter(name, self.get(name));
+        }
+    }
+}
+
+struct BuiltIn<const N: usize>;
+
+impl<const N: usize> BuiltIn<N> {
+    const EFFECTS: &'static [EffectFactory<N>] = &[
+        EffectFactory {
+            name: "Solid Color",
+            description: "The whole strip in one colour",
+            defaults: &[("red", 255.0), ("green", 0.0), ("blue", 0.0), ("white", 0.0)],
+            constructor: |params| {
+                Effect::SolidColor(SolidColor {
+                    color: RGB8::new(
+                        params.get("red") as u8,
+                        params.get("green") as u8,
+                        params.get("blue") as u8,
+                    ),
+                    white: params.get("white") as u8,
+                })
+            },
+        },
+        EffectFactory {
+            name: "PoliceDot",
+            description: "A red and a blue dot chasing each other",
+            defaults: &[("speed", 1.0), ("size", 2.0)],
+            constructor: |params| {
+                Effect::PoliceDot(PoliceDot::new(
+                    params.get("speed"),
+                    params.get("size") as usize,
+                    N,
+                ))
+            },
+        },
+        EffectFactory {
+            name: "PoliceTrail",
+            description: "Red and blue dots with fading trails",
+            defaults: &[("speed", 1.0), ("size", 2.0), ("trail_length", 8.0)],
+            constructor: |params| {
+                Effect::PoliceTrail(PoliceTrail::new(
+                    params.get("speed"),
+                    params.get("size") as usize,
+                    params.get("trail_length") as usize,
+                    N,
+                ))
+            },
+        },
+        EffectFactory {
+            name: "Drogen",
+            description: "Rainbow waves mirrored around the centre",
+            defaults: &[("speed", 0.4)],
+            constructor: |params| {
+                let mut effect = DrogenEffect::new(N);
+                params.apply(&mut effect);
+                Effect::Drogen(effect)
+            },
+        },
+        EffectFactory {
+            name: "Rocket",
+            description: "A rocket climbing the strip, leaving sparks behind",
+            defaults: &[
+                ("flight_time", 5.0),
+                ("rocket_size", 5.0),
+                ("boost_delay", 1.0),
+                ("boost_multiplier", 50.0),
+                ("exhaust_hue", 0.02),
+                ("rocket_hue", 0.0),
+                ("multi_color", 0.0),
+            ],
+            constructor: |params| {
+                let mut effect = RocketEffect::new();
+                params.apply(&mut effect);
+                Effect::Rocket(effect)
+            },
+        },
+    ];
+}
+
+/// The built-in effects for a strip of `N` LEDs, in the order they are
+/// offered to users.
+pub fn effects<const N: usize>() -> &'static [EffectFactory<N>] {
+    BuiltIn::<N>::EFFECTS
+}
+
+/// Looks up a built-in effect by name.
+pub fn find<const N: usize>(name: &str) -> Option<&'static EffectFactory<N>> {
+    effects().iter().find(|factory| factory.name == name)
+}
@@ -1,9 +1,8 @@
 use crate::effect::LedEffect;
+use crate::output::RGB16;
+use crate::palette::Palette;
 
-extern crate alloc;
-
-use alloc::vec;
-use alloc::vec::Vec;
+use heapless::Vec;
 use libm::{floorf, fmodf};
 use smart_leds::RGB8;
 
@@ -13,13 +12,73 @@ fn random_f32(seed: &mut u32) -> f32 {
     (*seed as f32) / (u32::MAX as f32)
 }
 
+/// Brightness of one colour channel in the pixel buffers, 1.0 is full.
+#[cfg(not(feature = "fixed-point"))]
+type Level = f32;
+/// Brightness of one colour channel in the pixel buffers, 0xFFFF is full.
+#[cfg(feature = "fixed-point")]
+type Level = u16;
+
+#[cfg(not(feature = "fixed-point"))]
+fn level(value: f32) -> Level {
+    value
+}
+
+#[cfg(feature = "fixed-point")]
+fn level(value: f32) -> Level {
+    (value.clamp(0.0, 1.0) * 65535.0) as Level
+}
+
+#[cfg(not(feature = "fixed-point"))]
+fn scale(level: Level, factor: Level) -> Level {
+    level * factor
+}
+
+#[cfg(feature = "fixed-point")]
+fn scale(level: Level, factor: Level) -> Level {
+    crate::fixed::scale16(level, factor)
+}
+
+#[cfg(not(feature = "fixed-point"))]
+fn add(level: Level, other: Level) -> Level {
+    level + other
+}
+
+#[cfg(feature = "fixed-point")]
+fn add(level: Level, other: Level) -> Level {
+    level.saturating_add(other)
+}
+
+#[cfg(not(feature = "fixed-point"))]
+fn to_u8(level: Level) -> u8 {
+    (level.clamp(0.0, 1.0) * 255.0) as u8
+}
+
+#[cfg(feature = "fixed-point")]
+fn to_u8(level: Level) -> u8 {
+    (level >> 8) as u8
+}
+
+/// `level` in 8.8 fixed point, full is 255.0.
+#[cfg(not(feature = "fixed-point"))]
+fn to_u16(level: Level) -> u16 {
+    (level.clamp(0.0, 1.0) * 65280.0) as u16
+}
+
+/// `level` in 8.8 fixed point, full is 255.0.
+#[cfg(feature = "fixed-point")]
+fn to_u16(level: Level) -> u16 {
+    level - (level >> 8)
+}
+
 struct Spark {
     energy: f32,
     pos: f32,
     hue: f32,
 }
 
-pub struct RocketEffect {
+/// Rocket climbing a strip of `N` LEDs, its pixel buffers are sized at compile time.
+pub struct RocketEffect<const N: usize> {
     // Parameters
     flight_time: f32,
     rocket_size: usize,
@@ -32,6 +91,7 @@ pub struct RocketEffect {
     rocket_sat: f32,
     rocket_val: f32,
     multi_color: bool,
+    palette: Option<&'static Palette>,
 
     // State
     rocket_pos: f32,
@@ -39,28 +99,35 @@ pub struct RocketEffect {
     elapsed_time: f32,
 
     // Spark simulation
-    sparks: Vec<Spark>,
+    sparks: Vec<Spark, N>,
     max_sparks: usize,
     friction: f32,
 
     // Pixel Buffers for additive blending
-    pixels_r: Vec<f32>,
-    pixels_g: Vec<f32>,
-    pixels_b: Vec<f32>,
+    pixels_r: [Level; N],
+    pixels_g: [Level; N],
+    pixels_b: [Level; N],
 
-    num_leds: usize,
     rng_seed: u32,
 }
 
-impl RocketEffect {
-    pub fn new(num_leds: usize) -> Self {
+impl<const N: usize> Default for RocketEffect<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> RocketEffect<N> {
+    pub fn new() -> Self {
+        let num_leds = N;
         let max_sparks = (num_leds / 6).max(1);
-        let mut sparks = Vec::with_capacity(max_sparks);
+        let mut sparks = Vec::new();
         let mut seed = 12345; // Initial seed
 
         for _ in 0..max_sparks {
             let pos = random_f32(&mut seed) * num_leds as f32;
-            sparks.push(Spark {
+            // Only an empty strip has no room for its single spark.
+            let _ = sparks.push(Spark {
                 pos,
                 energy: 1.0 * (1.0 - pos / num_leds as f32) + random_f32(&mut seed) * 0.4,
                 hue: random_f32(&mut seed),
@@ -79,6 +146,7 @@ impl RocketEffect {
             rocket_sat: 0.0,
             rocket_val: 1.0,
             multi_color: false,
+            palette: None,
 
             rocket_pos: 0.0,
             rocket_vel: 0.0,
@@ -88,11 +156,10 @@ impl RocketEffect {
             max_sparks,
             friction: 0.9 / num_leds as f32,
 
-            pixels_r: vec![0.0; num_leds],
-            pixels_g: vec![0.0; num_leds],
-            pixels_b: vec![0.0; num_leds],
+            pixels_r: [Level::default(); N],
+            pixels_g: [Level::default(); N],
+            pixels_b: [Level::default(); N],
 
-            num_leds,
             rng_seed: seed,
         }
     }
@@ -117,25 +184,69 @@ impl RocketEffect {
     }
 }
 
-impl LedEffect for RocketEffect {
+impl<const N: usize> LedEffect for RocketEffect<N> {
     fn name(&self) -> &str {
         "Rocket"
     }
 
+    fn parameters(&self) -> &'static [&'static str] {
+        &[
+            "flight_time",
+            "rocket_size",
+            "boost_delay",
+            "boost_multiplier",
+            "exhaust_hue",
+            "rocket_hue",
+            "multi_color",
+        ]
+    }
+
+    fn parameter(&self, name: &str) -> Option<f32> {
+        match name {
+            "flight_time" => Some(self.flight_time),
+            "rocket_size" => Some(self.rocket_size as f32),
+            "boost_delay" => Some(self.boost_delay),
+            "boost_multiplier" => Some(self.boost_multiplier),
+            "exhaust_hue" => Some(self.exhaust_hue),
+            "rocket_hue" => Some(self.rocket_hue),
+            "multi_color" => Some(if self.multi_color { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            // A zero flight time would divide by zero in the rocket physics.
+            "flight_time" => self.flight_time = value.max(0.1),
+            "rocket_size" => self.rocket_size = value.max(0.0) as usize,
+            "boost_delay" => self.boost_delay = value.max(0.0),
+            "boost_multiplier" => self.boost_multiplier = value.max(0.0),
+            "exhaust_hue" => self.exhaust_hue = value,
+            "rocket_hue" => self.rocket_hue = value,
+            "multi_color" => self.multi_color = value != 0.0,
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_palette(&mut self, palette: Option<&'static Palette>) {
+        self.palette = palette;
+    }
+
     fn before_render(&mut self, delta: f32) {
         let spark_delta = delta * 10.0;
 
         // 1. Cool all pixels
-        let cool_factor = (0.1 / spark_delta).min(0.99);
-        for i in 0..self.num_leds {
-            self.pixels_r[i] *= cool_factor;
-            self.pixels_g[i] *= cool_factor;
-            self.pixels_b[i] *= cool_factor;
+        let cool_factor = level((0.1 / spark_delta).min(0.99));
+        for i in 0..N {
+            self.pixels_r[i] = scale(self.pixels_r[i], cool_factor);
+            self.pixels_g[i] = scale(self.pixels_g[i], cool_factor);
+            self.pixels_b[i] = scale(self.pixels_b[i], cool_factor);
         }
 
         // 2. Update rocket physics
         self.elapsed_time += delta;
-        let base_accel = 2.0 * self.num_leds as f32 / (self.flight_time * self.flight_time);
+        let base_accel = 2.0 * N as f32 / (self.flight_time * self.flight_time);
         let current_accel = if self.elapsed_time > self.boost_delay {
             base_accel * self.boost_multiplier
         } else {
@@ -145,7 +256,7 @@ impl LedEffect for RocketEffect {
         self.rocket_pos += self.rocket_vel * delta;
 
         // Reset if it flies off the top
-        if self.rocket_pos >= self.num_leds as f32 {
+        if self.rocket_pos >= N as f32 {
             self.rocket_pos = 0.0;
             self.rocket_vel = 0.0;
             self.elapsed_time = 0.0;
@@ -171,14 +282,14 @@ impl LedEffect for RocketEffect {
             spark.energy = spark.energy.max(0.0);
             spark.pos -= spark.energy * spark.energy * spark_delta; // Move downward
 
-            if spark.pos < 0.0 || spark.pos >= self.num_leds as f32 {
+            if spark.pos < 0.0 || spark.pos >= N as f32 {
                 spark.pos = self.rocket_pos;
                 spark.energy = 0.0; // Mark for respawn on next frame
                 continue;
             }
 
             let spark_idx = floorf(spark.pos) as usize;
-            if spark_idx < self.num_leds {
+            if spark_idx < N {
                 let contrib_v = spark.energy * spark.energy; // Gamma
                 let mut h = if self.multi_color {
                     spark.hue
@@ -193,10 +304,16 @@ impl LedEffect for RocketEffect {
                 let s = (self.exhaust_sat * (1.1 - contrib_v)).clamp(0.0, 1.0);
                 let v = (contrib_v * self.exhaust_val).clamp(0.0, 1.0);
 
-                let (r, g, b) = Self::hsv_to_rgb(h, s, v);
-                self.pixels_r[spark_idx] += r;
-                self.pixels_g[spark_idx] += g;
-                self.pixels_b[spark_idx] += b;
+                let (r, g, b) = match self.palette {
+                    Some(palette) => {
+                        let c = palette.color_at(h);
+                        (c.r as f32 / 255.0 * v, c.g as f32 / 255.0 * v, c.b as f32 / 255.0 * v)
+                    }
+                    None => Self::hsv_to_rgb(h, s, v),
+                };
+                self.pixels_r[spark_idx] = add(self.pixels_r[spark_idx], level(r));
+                self.pixels_g[spark_idx] = add(self.pixels_g[spark_idx], level(g));
+                self.pixels_b[spark_idx] = add(self.pixels_b[spark_idx], level(b));
             }
         }
 
@@ -204,19 +321,28 @@ impl LedEffect for RocketEffect {
         let (r, g, b) = Self::hsv_to_rgb(self.rocket_hue, self.rocket_sat, self.rocket_val);
         for j in 0..self.rocket_size {
             let body_idx = floorf(self.rocket_pos + j as f32) as usize;
-            if body_idx < self.num_leds {
-                self.pixels_r[body_idx] = r; // Use direct assignment for solid body
-                self.pixels_g[body_idx] = g;
-                self.pixels_b[body_idx] = b;
+            if body_idx < N {
+                self.pixels_r[body_idx] = level(r); // Use direct assignment for solid body
+                self.pixels_g[body_idx] = level(g);
+                self.pixels_b[body_idx] = level(b);
             }
         }
     }
 
     fn render(&self, index: usize, _num_leds: usize) -> RGB8 {
-        let r = (self.pixels_r[index].clamp(0.0, 1.0) * 255.0) as u8;
-        let g = (self.pixels_g[index].clamp(0.0, 1.0) * 255.0) as u8;
-        let b = (self.pixels_b[index].clamp(0.0, 1.0) * 255.0) as u8;
-        RGB8 { r, g, b }
+        RGB8 {
+            r: to_u8(self.pixels_r[index]),
+            g: to_u8(self.pixels_g[index]),
+            b: to_u8(self.pixels_b[index]),
+        }
+    }
+
+    fn render16(&self, index: usize, _num_leds: usize) -> RGB16 {
+        RGB16 {
+            r: to_u16(self.pixels_r[index]),
+            g: to_u16(self.pixels_g[index]),
+            b: to_u16(self.pixels_b[index]),
+        }
     }
 }
 
@@ -1,10 +1,25 @@
 use smart_leds::{
-    RGB8
+    White, RGB8, RGBW
 };
 use crate::effect::LedEffect; 
+use crate::output::{RGBW8, WhiteMode};
 
 pub struct SolidColor {
     pub color: RGB8,
+    /// Level of the white channel on RGBW strips, mixed in as pure white on
+    /// RGB strips.
+    pub white: u8,
+}
+
+impl SolidColor {
+    fn rgbw(&self) -> RGBW8 {
+        RGBW {
+            r: self.color.r,
+            g: self.color.g,
+            b: self.color.b,
+            a: White(self.white),
+        }
+    }
 }
 
 impl LedEffect for SolidColor {
@@ -13,11 +28,40 @@ impl LedEffect for SolidColor {
     }
 
     fn render(&self, _index: usize, _num_leds: usize) -> RGB8 {
-        self.color
+        WhiteMode::MinChannel.combine(self.rgbw())
+    }
+
+    fn render_rgbw(&self, _index: usize, _num_leds: usize) -> Option<RGBW8> {
+        Some(self.rgbw())
     }
 
     fn name(&self) -> &str {
         "Solid Color"
     }
-}
 
+    fn parameters(&self) -> &'static [&'static str] {
+        &["red", "green", "blue", "white"]
+    }
+
+    fn parameter(&self, name: &str) -> Option<f32> {
+        match name {
+            "red" => Some(self.color.r as f32),
+            "green" => Some(self.color.g as f32),
+            "blue" => Some(self.color.b as f32),
+            "white" => Some(self.white as f32),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let value = value.clamp(0.0, 255.0) as u8;
+        match name {
+            "red" => self.color.r = value,
+            "green" => self.color.g = value,
+            "blue" => self.color.b = value,
+            "white" => self.white = value,
+            _ => return false,
+        }
+        true
+    }
+}
@@ -0,0 +1,41 @@
+/// Tracks whether an external frame source (lighting desk, PC, ...) is still
+/// driving the strip, so the firmware can fall back to local effects when
+/// the stream stops.
+pub struct StreamTimeout {
+    timeout: f32,
+    idle: f32,
+}
+
+impl StreamTimeout {
+    /// `timeout` is the time in seconds without frames after which the
+    /// stream is considered gone.
+    pub const fn new(timeout: f32) -> Self {
+        Self {
+            timeout,
+            // Start inactive until the first frame arrives.
+            idle: timeout,
+        }
+    }
+
+    /// Called whenever a complete frame has been received.
+    pub fn frame_received(&mut self) {
+        self.idle = 0.0;
+    }
+
+    /// Called when the source announces that it stopped sending.
+    pub fn stop(&mut self) {
+        self.idle = self.timeout;
+    }
+
+    /// Called once per render loop with the time in seconds since the last call.
+    pub fn update(&mut self, delta: f32) {
+        if self.idle < self.timeout {
+            self.idle += delta;
+        }
+    }
+
+    /// Returns true while frames keep arriving within the timeout.
+    pub fn is_active(&self) -> bool {
+        self.idle < self.timeout
+    }
+}
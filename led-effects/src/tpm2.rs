@@ -0,0 +1,105 @@
+use smart_leds::RGB8;
+
+const START_BYTE: u8 = 0xC9;
+const END_BYTE: u8 = 0x36;
+/// Frame type of a TPM2 data frame; command (0xC0) and response (0xAA)
+/// frames are skipped.
+const FRAME_TYPE_DATA: u8 = 0xDA;
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Idle,
+    FrameType,
+    SizeHigh,
+    SizeLow,
+    Data,
+    End,
+}
+
+/// Byte-at-a-time decoder for the TPM2 serial protocol.
+///
+/// ```text
+/// 0xC9 | frame type | size hi | size lo | R G B ... | 0x36
+/// ```
+///
+/// Up to `N` LEDs are kept, colours for LEDs beyond that are read and dropped.
+pub struct Tpm2Decoder<const N: usize> {
+    state: State,
+    frame_type: u8,
+    size: usize,
+    received: usize,
+    pixels: [RGB8; N],
+}
+
+impl<const N: usize> Tpm2Decoder<N> {
+    pub fn new() -> Self {
+        Self {
+            state: State::Idle,
+            frame_type: 0,
+            size: 0,
+            received: 0,
+            pixels: [RGB8::default(); N],
+        }
+    }
+
+    /// Feeds one received byte into the decoder.
+    /// Returns true when the byte completed a data frame, which can then be
+    /// read with `pixels()` until the next byte is fed.
+    pub fn feed(&mut self, byte: u8) -> bool {
+        match self.state {
+            State::Idle => {
+                if byte == START_BYTE {
+                    self.state = State::FrameType;
+                }
+            }
+            State::FrameType => {
+                self.frame_type = byte;
+                self.state = State::SizeHigh;
+            }
+            State::SizeHigh => {
+                self.size = (byte as usize) << 8;
+                self.state = State::SizeLow;
+            }
+            State::SizeLow => {
+                self.size |= byte as usize;
+                self.received = 0;
+                if self.frame_type == FRAME_TYPE_DATA {
+                    self.pixels = [RGB8::default(); N];
+                }
+                self.state = if self.size == 0 { State::End } else { State::Data };
+            }
+            State::Data => {
+                if self.frame_type == FRAME_TYPE_DATA {
+                    let index = self.received / 3;
+                    if let Some(pixel) = self.pixels.get_mut(index) {
+                        match self.received % 3 {
+                            0 => pixel.r = byte,
+                            1 => pixel.g = byte,
+                            _ => pixel.b = byte,
+                        }
+                    }
+                }
+                self.received += 1;
+                if self.received == self.size {
+                    self.state = State::End;
+                }
+            }
+            State::End => {
+                self.state = State::Idle;
+                return byte == END_BYTE && self.frame_type == FRAME_TYPE_DATA;
+            }
+        }
+        false
+    }
+
+    /// LED colours of the last completed data frame.
+    pub fn pixels(&self) -> &[RGB8] {
+        &self.pixels
+    }
+}
+
+impl<const N: usize> Default for Tpm2Decoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
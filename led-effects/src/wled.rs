@@ -0,0 +1,254 @@
+//! Subset of the WLED JSON API (`/json/state`, `/json/info`, ...) so WLED
+//! apps and integrations can drive the controller.
+//!
+//! The controller is presented as a single WLED segment covering the strip:
+//! `fx` indexes the registered effects, `pal` indexes `PALETTES`, the first
+//! `col` entry is the effect colour and `bri` the global brightness.
+
+use core::fmt::Write;
+
+use heapless::{String, Vec};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use smart_leds::RGB8;
+
+use crate::controller::EffectController;
+use crate::http_api::EffectList;
+use crate::palette::PALETTES;
+
+/// WLED version reported to clients, the subset implemented matches its API.
+const WLED_VERSION: &str = "0.14.0";
+const WLED_VERSION_ID: u32 = 2_310_130;
+
+/// Static facts about the device reported by `/json/info`.
+pub struct DeviceInfo<'a> {
+    pub name: &'a str,
+    pub num_leds: usize,
+    pub mac: [u8; 6],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WledError {
+    /// The body is not a WLED state object.
+    InvalidJson,
+    /// `fx` is not the index of a registered effect.
+    UnknownEffect,
+    /// `pal` is not the index of a palette.
+    UnknownPalette,
+}
+
+#[derive(Deserialize)]
+struct StateUpdate {
+    on: Option<bool>,
+    bri: Option<u8>,
+    seg: Option<Vec<SegmentUpdate, 4>>,
+    /// Answer with the full state instead of `{"success":true}`.
+    v: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct SegmentUpdate {
+    id: Option<u8>,
+    fx: Option<usize>,
+    pal: Option<usize>,
+    col: Option<Vec<Vec<u8, 4>, 3>>,
+}
+
+#[derive(Serialize)]
+struct State {
+    on: bool,
+    bri: u8,
+    transition: u8,
+    ps: i8,
+    pl: i8,
+    seg: [Segment; 1],
+}
+
+#[derive(Serialize)]
+struct Segment {
+    id: u8,
+    start: usize,
+    stop: usize,
+    len: usize,
+    on: bool,
+    bri: u8,
+    fx: usize,
+    pal: usize,
+    col: [[u8; 3]; 3],
+}
+
+#[derive(Serialize)]
+struct Leds {
+    count: usize,
+    rgbw: bool,
+    maxseg: u8,
+}
+
+#[derive(Serialize)]
+struct Info<'a> {
+    ver: &'a str,
+    vid: u32,
+    leds: Leds,
+    name: &'a str,
+    fxcount: usize,
+    palcount: usize,
+    brand: &'a str,
+    product: &'a str,
+    mac: String<12>,
+}
+
+#[derive(Serialize)]
+struct Full<'a> {
+    state: State,
+    info: Info<'a>,
+    effects: EffectList,
+    palettes: PaletteList,
+}
+
+#[derive(Serialize)]
+struct Success {
+    success: bool,
+}
+
+/// Serializes the palette names as a JSON array.
+struct PaletteList;
+
+impl Serialize for PaletteList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(PALETTES.iter().map(|palette| palette.name))
+    }
+}
+
+fn current_state<const N: usize>(controller: &EffectController<N>, device: &DeviceInfo) -> State {
+    let color = controller.color().unwrap_or_default();
+    State {
+        on: controller.is_on(),
+        bri: controller.brightness(),
+        transition: 0,
+        ps: -1,
+        pl: -1,
+        seg: [Segment {
+            id: 0,
+            start: 0,
+            stop: device.num_leds,
+            len: device.num_leds,
+            on: true,
+            bri: 255,
+            fx: controller.current_effect_index(),
+            pal: controller.palette_index(),
+            col: [[color.r, color.g, color.b], [0; 3], [0; 3]],
+        }],
+    }
+}
+
+fn current_info<'a, const N: usize>(controller: &EffectController<N>, device: &DeviceInfo<'a>) -> Info<'a> {
+    let mut mac = String::new();
+    for byte in device.mac {
+        let _ = write!(mac, "{byte:02x}");
+    }
+    Info {
+        ver: WLED_VERSION,
+        vid: WLED_VERSION_ID,
+        leds: Leds {
+            count: device.num_leds,
+            rgbw: false,
+            maxseg: 1,
+        },
+        name: device.name,
+        fxcount: controller.len(),
+        palcount: PALETTES.len(),
+        brand: "WLED",
+        product: "led-effects",
+        mac,
+    }
+}
+
+/// Writes `/json/state`.
+pub fn state<const N: usize>(
+    controller: &EffectController<N>,
+    device: &DeviceInfo,
+    out: &mut [u8],
+) -> Result<usize, serde_json_core::ser::Error> {
+    serde_json_core::to_slice(&current_state(controller, device), out)
+}
+
+/// Writes `/json/info`.
+pub fn info<const N: usize>(
+    controller: &EffectController<N>,
+    device: &DeviceInfo,
+    out: &mut [u8],
+) -> Result<usize, serde_json_core::ser::Error> {
+    serde_json_core::to_slice(&current_info(controller, device), out)
+}
+
+/// Writes `/json/palettes`.
+pub fn palettes(out: &mut [u8]) -> Result<usize, serde_json_core::ser::Error> {
+    serde_json_core::to_slice(&PaletteList, out)
+}
+
+/// Writes `/json`: state, info, effect and palette names in one object.
+pub fn full<const N: usize>(
+    controller: &EffectController<N>,
+    device: &DeviceInfo,
+    out: &mut [u8],
+) -> Result<usize, serde_json_core::ser::Error> {
+    let full = Full {
+        state: current_state(controller, device),
+        info: current_info(controller, device),
+        effects: EffectList::new(controller),
+        palettes: PaletteList,
+    };
+    serde_json_core::to_slice(&full, out)
+}
+
+/// Writes the `{"success":true}` reply to a state update.
+pub fn success(out: &mut [u8]) -> Result<usize, serde_json_core::ser::Error> {
+    serde_json_core::to_slice(&Success { success: true }, out)
+}
+
+/// Applies a state update POSTed to `/json/state`. Returns true if the
+/// client asked for the new state in the reply (`"v":true`).
+///
+/// Effect and palette indices are checked before anything is changed.
+pub fn apply_state<const N: usize>(controller: &mut EffectController<N>, body: &[u8]) -> Result<bool, WledError> {
+    let (update, _): (StateUpdate, _) =
+        serde_json_core::from_slice(body).map_err(|_| WledError::InvalidJson)?;
+
+    // Only segment 0 exists, updates without an id address it too.
+    let segment = update
+        .seg
+        .as_ref()
+        .and_then(|segments| segments.iter().find(|segment| segment.id.unwrap_or(0) == 0));
+
+    if let Some(segment) = segment {
+        if segment.fx.is_some_and(|fx| fx >= controller.len()) {
+            return Err(WledError::UnknownEffect);
+        }
+        if segment.pal.is_some_and(|pal| pal >= PALETTES.len()) {
+            return Err(WledError::UnknownPalette);
+        }
+        if let Some(fx) = segment.fx {
+            controller
+                .set_effect_by_index(fx)
+                .map_err(|_| WledError::UnknownEffect)?;
+        }
+        if let Some(pal) = segment.pal {
+            controller.set_palette(pal);
+        }
+        // The colour goes to the requested effect, which ignores it if it
+        // takes none. Without `fx` a colour may switch to a colour effect.
+        let primary = segment.col.as_ref().and_then(|col| col.first());
+        if let Some(&[r, g, b, ..]) = primary.map(|color| &color[..]) {
+            if segment.fx.is_none() || controller.color().is_some() {
+                controller.set_color(RGB8::new(r, g, b));
+            }
+        }
+    }
+    if let Some(bri) = update.bri {
+        controller.set_brightness(bri);
+    }
+    if let Some(on) = update.on {
+        controller.set_on(on);
+    }
+    Ok(update.v == Some(true))
+}
@@ -0,0 +1,80 @@
+//! Helpers shared by the API tests.
+
+// Not every test file uses every helper.
+#![allow(dead_code)]
+
+use led_effects::controller::EffectController;
+use led_effects::http_api::{self, ParseError, Request, Status};
+use led_effects::registry;
+use led_effects::wled::DeviceInfo;
+
+pub const NUM_LEDS: usize = 16;
+
+pub const DEVICE: DeviceInfo = DeviceInfo {
+    name: "LED Strip",
+    num_leds: NUM_LEDS,
+    mac: [0x24, 0x6F, 0x28, 0x0A, 0xB1, 0xC2],
+};
+
+/// A controller with the built-in effects, as the firmware sets it up.
+pub fn controller() -> EffectController<NUM_LEDS> {
+    let mut controller = EffectController::new();
+    for factory in registry::effects() {
+        controller.register(factory).unwrap();
+    }
+    controller
+}
+
+/// Stands in for the firmware's HTTP server: `request` arrives in reads of
+/// `chunk` bytes into the same buffers, and the response is returned as
+/// status code and body.
+pub fn serve_in_chunks(
+    controller: &mut EffectController<NUM_LEDS>,
+    request: &[u8],
+    chunk: usize,
+) -> (u16, String) {
+    let mut buffer = [0u8; http_api::MAX_REQUEST];
+    let mut response = [0u8; 1536];
+    let mut received = 0;
+    let len = loop {
+        let end = (received + chunk).min(request.len());
+        if end == received {
+            panic!("connection closed before the request was complete");
+        }
+        buffer[received..end].copy_from_slice(&request[received..end]);
+        received = end;
+        match Request::parse(&buffer[..received]) {
+            Ok(parsed) => break http_api::respond(controller, &DEVICE, &parsed, &mut response),
+            Err(ParseError::Incomplete) if received < buffer.len() => {}
+            Err(_) => break http_api::respond_error(Status::BadRequest, &mut response),
+        }
+    };
+    let response = std::str::from_utf8(&response[..len]).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head[9..12].parse().unwrap();
+    let length = format!("Content-Length: {}\r\n", body.len());
+    assert!(head.contains(&length), "{head}");
+    (status, body.to_string())
+}
+
+pub fn serve(controller: &mut EffectController<NUM_LEDS>, request: &[u8]) -> (u16, String) {
+    serve_in_chunks(controller, request, request.len())
+}
+
+/// A POST request with a JSON `body`, as curl sends it.
+pub fn post(path: &str, body: &str) -> Vec<u8> {
+    format!(
+        "POST {path} HTTP/1.1\r\nHost: 192.168.4.1\r\nUser-Agent: curl/8.5.0\r\n\
+         Accept: */*\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .into_bytes()
+}
+
+/// A GET request, as curl sends it.
+pub fn get(path: &str) -> Vec<u8> {
+    format!(
+        "GET {path} HTTP/1.1\r\nHost: 192.168.4.1\r\nUser-Agent: curl/8.5.0\r\nAccept: */*\r\n\r\n"
+    )
+    .into_bytes()
+}
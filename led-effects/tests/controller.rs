@@ -0,0 +1,168 @@
+//! Brightness scaling and temporal dithering of the controller output.
+
+use led_effects::controller::EffectController;
+use led_effects::output::{RGB16, RGBW8};
+use led_effects::registry;
+use smart_leds::{White, RGB8};
+
+/// Sums each channel of LED 0 over `frames` frames of `color` at `level`.
+fn dithered_sum(level: u8, color: RGB8, frames: usize) -> [u32; 3] {
+    let mut controller = EffectController::<1>::new();
+    controller.set_brightness(level);
+    let mut sum = [0; 3];
+    for _ in 0..frames {
+        let mut leds = [color];
+        controller.apply_output_level(&mut leds);
+        sum[0] += leds[0].r as u32;
+        sum[1] += leds[0].g as u32;
+        sum[2] += leds[0].b as u32;
+    }
+    sum
+}
+
+#[test]
+fn average_matches_level() {
+    // 100 at level 3 is 400 / 256, about 1.56: 8 bit alone would show 1.
+    assert_eq!(
+        dithered_sum(3, RGB8::new(100, 100, 100), 256),
+        [400, 400, 400]
+    );
+    for level in [1, 3, 17, 128, 254] {
+        for value in [1, 7, 100, 255] {
+            let sum = dithered_sum(level, RGB8::new(value, 0, 0), 256)[0];
+            assert_eq!(sum, value as u32 * (level as u32 + 1), "{value} at {level}");
+        }
+    }
+}
+
+#[test]
+fn full_level_is_exact() {
+    let mut controller = EffectController::<3>::new();
+    let colors = [
+        RGB8::new(255, 0, 1),
+        RGB8::new(17, 128, 254),
+        RGB8::new(0, 0, 0),
+    ];
+    for _ in 0..10 {
+        let mut leds = colors;
+        controller.apply_output_level(&mut leds);
+        assert_eq!(leds, colors);
+    }
+}
+
+#[test]
+fn frame_keeps_the_scaled_values() {
+    let mut controller = EffectController::<2>::new();
+    controller.set_brightness(3);
+    let mut leds = [RGB8::new(100, 1, 0), RGB8::new(255, 255, 255)];
+    controller.apply_output_level(&mut leds);
+    assert_eq!(controller.frame()[0], RGB16 { r: 400, g: 4, b: 0 });
+    assert_eq!(
+        controller.frame()[1],
+        RGB16 {
+            r: 1020,
+            g: 1020,
+            b: 1020
+        }
+    );
+}
+
+#[test]
+fn off_is_black() {
+    let white = RGB8::new(255, 255, 255);
+    assert_eq!(dithered_sum(0, white, 512), [0, 0, 0]);
+    let mut controller = EffectController::<1>::new();
+    controller.set_on(false);
+    for _ in 0..512 {
+        let mut leds = [white];
+        controller.apply_output_level(&mut leds);
+        assert_eq!(leds[0], RGB8::default());
+    }
+}
+
+#[test]
+fn rgbw_white_is_dithered_too() {
+    let mut controller = EffectController::<1>::new();
+    controller.set_brightness(3);
+    let mut sum = [0; 4];
+    for _ in 0..256 {
+        let mut leds = [RGBW8 {
+            r: 100,
+            g: 0,
+            b: 7,
+            a: White(100),
+        }];
+        controller.apply_output_level_rgbw(&mut leds);
+        let led = leds[0];
+        for (sum, value) in sum.iter_mut().zip([led.r, led.g, led.b, led.a.0]) {
+            *sum += value as u32;
+        }
+    }
+    assert_eq!(sum, [400, 0, 28, 400]);
+    assert_eq!(
+        controller.frame()[0],
+        RGB16 {
+            r: 400,
+            g: 0,
+            b: 28
+        }
+    );
+
+    controller.set_brightness(255);
+    let white = RGBW8 {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: White(255),
+    };
+    let mut leds = [white];
+    controller.apply_output_level_rgbw(&mut leds);
+    assert_eq!(leds, [white]);
+}
+
+#[test]
+fn trail_fade_is_dithered_below_8_bit() {
+    let mut controller = EffectController::<16>::new();
+    for factory in registry::effects() {
+        controller.register(factory).unwrap();
+    }
+    controller.set_effect_by_name("PoliceTrail").unwrap();
+    let effect = controller.get_current_effect().unwrap();
+    for (name, value) in [("speed", 0.0), ("size", 1.0), ("trail_length", 6.0)] {
+        assert!(effect.set_parameter(name, value));
+    }
+    controller.set_brightness(3);
+
+    // LED 14 is a step into the trail of the red dot on LED 0: 5/6 of 255
+    // is 212.5, times 4 / 256 at level 3. Scaling 8 bit 212 would give 848.
+    let mut sum = 0;
+    for _ in 0..256 {
+        let mut leds = [RGB8::default(); 16];
+        controller.render_output(0.05, &mut leds);
+        sum += leds[14].r as u32;
+    }
+    assert_eq!(controller.frame()[14].r, 850);
+    assert_eq!(sum, 850);
+}
+
+#[test]
+fn render16_widens_render() {
+    let mut controller = EffectController::<16>::new();
+    for factory in registry::effects() {
+        controller.register(factory).unwrap();
+    }
+    for index in 0..controller.len() {
+        controller.set_effect_by_index(index).unwrap();
+        let effect = controller.get_current_effect().unwrap();
+        for _ in 0..50 {
+            effect.before_render(0.05);
+            for i in 0..16 {
+                let (color, wide) = (effect.render(i, 16), effect.render16(i, 16));
+                let high = [wide.r >> 8, wide.g >> 8, wide.b >> 8];
+                for (high, value) in high.into_iter().zip([color.r, color.g, color.b]) {
+                    assert!(high.abs_diff(value as u16) <= 1, "{}", effect.name());
+                }
+            }
+        }
+    }
+}
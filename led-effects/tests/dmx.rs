@@ -0,0 +1,121 @@
+//! DMX frames as captured from an Enttec DMX USB Pro compatible sender.
+
+use led_effects::dmx::{DmxMapping, DmxReceiver};
+use led_effects::stream::StreamTimeout;
+use smart_leds::RGB8;
+
+/// Label 6, 7 bytes: start code and two LEDs, red and dim blue.
+const TWO_LEDS: [u8; 12] = [
+    0x7E, 0x06, 0x07, 0x00, 0x00, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x40, 0xE7,
+];
+
+/// Feeds `bytes`, returning the number of frames they completed.
+fn feed(receiver: &mut DmxReceiver, bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&byte| receiver.feed(byte)).count()
+}
+
+#[test]
+fn frame() {
+    let mut receiver = DmxReceiver::new();
+    assert_eq!(feed(&mut receiver, &TWO_LEDS), 1);
+    assert_eq!(receiver.channels(), &[0xFF, 0, 0, 0, 0, 0x40]);
+
+    let mapping = DmxMapping::new(1);
+    assert_eq!(mapping.pixel(receiver.channels(), 0), RGB8::new(0xFF, 0, 0));
+    assert_eq!(mapping.pixel(receiver.channels(), 1), RGB8::new(0, 0, 0x40));
+    assert_eq!(mapping.pixel(receiver.channels(), 2), RGB8::default());
+}
+
+#[test]
+fn full_universe() {
+    let mut bytes = vec![0x7E, 0x06, 0x01, 0x02, 0x00];
+    bytes.extend((0..512).map(|channel| channel as u8));
+    bytes.push(0xE7);
+    let mut receiver = DmxReceiver::new();
+    assert_eq!(feed(&mut receiver, &bytes), 1);
+    assert_eq!(receiver.channels().len(), 512);
+    assert_eq!(receiver.channels()[511], 0xFF);
+
+    // Channel 4 on the desk is the red of the first LED.
+    let mapping = DmxMapping::new(4);
+    assert_eq!(mapping.pixel(receiver.channels(), 0), RGB8::new(3, 4, 5));
+    assert_eq!(
+        mapping.pixel(receiver.channels(), 168),
+        RGB8::new(251, 252, 253)
+    );
+    // Only two of its channels are in the universe.
+    assert_eq!(mapping.pixel(receiver.channels(), 169), RGB8::default());
+}
+
+#[test]
+fn split_across_reads() {
+    let mut receiver = DmxReceiver::new();
+    for split in 0..TWO_LEDS.len() {
+        let (first, second) = TWO_LEDS.split_at(split);
+        assert_eq!(feed(&mut receiver, first), 0, "split at {split}");
+        assert_eq!(feed(&mut receiver, second), 1, "split at {split}");
+        assert_eq!(receiver.channels(), &[0xFF, 0, 0, 0, 0, 0x40]);
+    }
+}
+
+#[test]
+fn back_to_back_and_noise() {
+    let mut bytes = vec![0x00, 0x13, 0xE7];
+    bytes.extend(TWO_LEDS);
+    bytes.extend([0x7E, 0x06, 0x04, 0x00, 0x00, 0x01, 0x02, 0x03, 0xE7]);
+    let mut receiver = DmxReceiver::new();
+    assert_eq!(feed(&mut receiver, &bytes), 2);
+    assert_eq!(receiver.channels(), &[1, 2, 3]);
+}
+
+#[test]
+fn bad_end_byte() {
+    let mut bytes = TWO_LEDS;
+    bytes[11] = 0x00;
+    let mut receiver = DmxReceiver::new();
+    assert_eq!(feed(&mut receiver, &bytes), 0);
+    assert!(receiver.channels().is_empty());
+    // The next frame is picked up again.
+    assert_eq!(feed(&mut receiver, &TWO_LEDS), 1);
+}
+
+#[test]
+fn other_labels_and_start_codes() {
+    let mut receiver = DmxReceiver::new();
+    // Label 3, "Get Widget Parameters Reply", with a 5 byte payload.
+    let parameters = [0x7E, 0x03, 0x05, 0x00, 0x00, 0x01, 0x09, 0x01, 0x28, 0xE7];
+    assert_eq!(feed(&mut receiver, &parameters), 0);
+    // RDM start code 0xCC.
+    let mut rdm = TWO_LEDS;
+    rdm[4] = 0xCC;
+    assert_eq!(feed(&mut receiver, &rdm), 0);
+    assert!(receiver.channels().is_empty());
+    assert_eq!(feed(&mut receiver, &TWO_LEDS), 1);
+}
+
+#[test]
+fn oversize_and_empty() {
+    let mut receiver = DmxReceiver::new();
+    // 514 bytes is one more than a start code and a full universe.
+    assert_eq!(
+        feed(&mut receiver, &[0x7E, 0x06, 0x02, 0x02, 0x00, 0xE7]),
+        0
+    );
+    // No start code at all.
+    assert_eq!(feed(&mut receiver, &[0x7E, 0x06, 0x00, 0x00, 0xE7]), 0);
+    assert_eq!(feed(&mut receiver, &TWO_LEDS), 1);
+}
+
+#[test]
+fn timeout() {
+    let mut timeout = StreamTimeout::new(1.0);
+    assert!(!timeout.is_active());
+    timeout.frame_received();
+    timeout.update(0.6);
+    assert!(timeout.is_active());
+    timeout.update(0.6);
+    assert!(!timeout.is_active());
+    timeout.frame_received();
+    timeout.stop();
+    assert!(!timeout.is_active());
+}
@@ -0,0 +1,149 @@
+//! E1.31 packets as captured from sACNView sending universe 1.
+
+use led_effects::e131::{multicast_address, E131Action, E131Error, E131Packet, E131Receiver};
+use smart_leds::RGB8;
+
+/// Six channels, two LEDs: red and dim blue, priority 100, sequence 42.
+const PACKET: [u8; 132] = [
+    0x00, 0x10, 0x00, 0x00, 0x41, 0x53, 0x43, 0x2D, 0x45, 0x31, 0x2E, 0x31, 0x37, 0x00, 0x00, 0x00,
+    0x70, 0x74, 0x00, 0x00, 0x00, 0x04, 0x5B, 0x8F, 0xF2, 0xA2, 0x6B, 0x14, 0x4B, 0xF6, 0xA8, 0xD9,
+    0xD1, 0xB1, 0xE2, 0xA5, 0xC3, 0xF0, 0x70, 0x5E, 0x00, 0x00, 0x00, 0x02, 0x73, 0x41, 0x43, 0x4E,
+    0x56, 0x69, 0x65, 0x77, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x64, 0x00, 0x00, 0x2A,
+    0x00, 0x00, 0x01, 0x70, 0x11, 0x02, 0xA1, 0x00, 0x00, 0x00, 0x01, 0x00, 0x07, 0x00, 0xFF, 0x00,
+    0x00, 0x00, 0x00, 0x40,
+];
+
+const SEQUENCE: usize = 111;
+const OPTIONS: usize = 112;
+const UNIVERSE: usize = 114;
+const START_CODE: usize = 125;
+
+/// `PACKET` with sequence number `sequence`.
+fn numbered(sequence: u8) -> [u8; 132] {
+    let mut bytes = PACKET;
+    bytes[SEQUENCE] = sequence;
+    bytes
+}
+
+fn accept(receiver: &mut E131Receiver, bytes: &[u8]) -> E131Action {
+    receiver.accept(&E131Packet::parse(bytes).unwrap())
+}
+
+#[test]
+fn parse() {
+    let packet = E131Packet::parse(&PACKET).unwrap();
+    assert_eq!(packet.universe, 1);
+    assert_eq!(packet.priority, 100);
+    assert_eq!(packet.sequence, 42);
+    assert_eq!(packet.start_code, 0);
+    assert_eq!(packet.channels, &[0xFF, 0, 0, 0, 0, 0x40]);
+    assert!(!packet.is_preview());
+    assert!(!packet.is_stream_terminated());
+
+    let receiver = E131Receiver::new(1, 1);
+    assert_eq!(receiver.pixel(&packet, 0), RGB8::new(0xFF, 0, 0));
+    assert_eq!(receiver.pixel(&packet, 1), RGB8::new(0, 0, 0x40));
+    assert_eq!(receiver.pixel(&packet, 2), RGB8::default());
+}
+
+#[test]
+fn truncated() {
+    for len in [0, 16, 125, 126, 131] {
+        let result = E131Packet::parse(&PACKET[..len]);
+        assert_eq!(result.unwrap_err(), E131Error::Truncated, "{len} bytes");
+    }
+    // No property values, not even the start code.
+    let mut bytes = PACKET;
+    bytes[124] = 0x00;
+    assert_eq!(E131Packet::parse(&bytes).unwrap_err(), E131Error::Truncated);
+    // Trailing bytes after the announced values are not channels.
+    let mut bytes = PACKET.to_vec();
+    bytes.extend([0x12, 0x34]);
+    assert_eq!(E131Packet::parse(&bytes).unwrap().channels.len(), 6);
+}
+
+#[test]
+fn not_e131() {
+    let not_e131 = |offset: usize, byte: u8| {
+        let mut bytes = PACKET;
+        bytes[offset] = byte;
+        E131Packet::parse(&bytes).unwrap_err()
+    };
+    // ACN packet identifier.
+    assert_eq!(not_e131(4, b'B'), E131Error::NotE131);
+    assert_eq!(not_e131(15, 0x01), E131Error::NotE131);
+    // Root vector 8, extended (sync and discovery) packets.
+    assert_eq!(not_e131(21, 0x08), E131Error::NotE131);
+    // Framing vector 1, a sync packet.
+    assert_eq!(not_e131(43, 0x01), E131Error::NotE131);
+    // DMP vector and address type.
+    assert_eq!(not_e131(117, 0x01), E131Error::NotE131);
+    assert_eq!(not_e131(118, 0xA0), E131Error::NotE131);
+}
+
+#[test]
+fn universes_and_options() {
+    let mut receiver = E131Receiver::new(1, 1);
+    let mut bytes = numbered(1);
+    bytes[UNIVERSE] = 2;
+    assert_eq!(accept(&mut receiver, &bytes), E131Action::Ignore);
+
+    let mut bytes = numbered(2);
+    bytes[OPTIONS] = 0x80;
+    assert!(E131Packet::parse(&bytes).unwrap().is_preview());
+    assert_eq!(accept(&mut receiver, &bytes), E131Action::Ignore);
+
+    // Alternate start codes count for the sequence but are not shown.
+    let mut bytes = numbered(3);
+    bytes[START_CODE] = 0xDD;
+    assert_eq!(accept(&mut receiver, &bytes), E131Action::Ignore);
+    assert_eq!(accept(&mut receiver, &numbered(3)), E131Action::Ignore);
+    assert_eq!(accept(&mut receiver, &numbered(4)), E131Action::Frame);
+
+    let mut bytes = numbered(5);
+    bytes[OPTIONS] = 0x40;
+    assert_eq!(accept(&mut receiver, &bytes), E131Action::Stop);
+    // After a stop any sequence number starts the stream again.
+    assert_eq!(accept(&mut receiver, &numbered(1)), E131Action::Frame);
+}
+
+#[test]
+fn sequence() {
+    let mut receiver = E131Receiver::new(1, 1);
+    assert_eq!(accept(&mut receiver, &numbered(42)), E131Action::Frame);
+    assert_eq!(accept(&mut receiver, &numbered(43)), E131Action::Frame);
+    // Repeated and late packets are dropped.
+    assert_eq!(accept(&mut receiver, &numbered(43)), E131Action::Ignore);
+    assert_eq!(accept(&mut receiver, &numbered(40)), E131Action::Ignore);
+    assert_eq!(accept(&mut receiver, &numbered(24)), E131Action::Ignore);
+    // 20 or more behind is a restarted source.
+    assert_eq!(accept(&mut receiver, &numbered(23)), E131Action::Frame);
+    // Skipped packets are fine.
+    assert_eq!(accept(&mut receiver, &numbered(30)), E131Action::Frame);
+}
+
+#[test]
+fn sequence_wraps() {
+    let mut receiver = E131Receiver::new(1, 1);
+    for sequence in (250..=255).chain(0..5) {
+        assert_eq!(
+            accept(&mut receiver, &numbered(sequence)),
+            E131Action::Frame,
+            "{sequence}"
+        );
+    }
+    // Behind across the wrap.
+    assert_eq!(accept(&mut receiver, &numbered(254)), E131Action::Ignore);
+    assert_eq!(accept(&mut receiver, &numbered(4)), E131Action::Ignore);
+    assert_eq!(accept(&mut receiver, &numbered(5)), E131Action::Frame);
+}
+
+#[test]
+fn multicast() {
+    assert_eq!(multicast_address(1), [239, 255, 0, 1]);
+    assert_eq!(multicast_address(256), [239, 255, 1, 0]);
+    assert_eq!(multicast_address(63999), [239, 255, 249, 255]);
+}
@@ -0,0 +1,135 @@
+//! The fixed-point helpers against their float counterparts.
+
+use core::f32::consts::PI;
+
+use led_effects::fixed::{
+    beat16, beat8, beatsin8, cos8, hsv8, lerp8, qadd8, qsub8, scale16, scale8, sin8, Q16,
+};
+use smart_leds::RGB8;
+
+#[test]
+fn sin8_close_to_sinf() {
+    for theta in 0..=255u8 {
+        let expected = 128.0 + 127.0 * libm::sinf(theta as f32 * 2.0 * PI / 256.0);
+        let error = (sin8(theta) as f32 - expected).abs();
+        assert!(error <= 3.5, "{theta}: {} vs {expected}", sin8(theta));
+    }
+}
+
+#[test]
+fn sin8_landmarks() {
+    assert_eq!(sin8(0), 128);
+    assert_eq!(sin8(64), 255);
+    assert_eq!(sin8(128), 128);
+    assert_eq!(sin8(192), 1);
+    assert_eq!(cos8(0), 255);
+    assert_eq!(cos8(64), 128);
+    // Symmetric around the quarter turns.
+    for theta in 0..64u8 {
+        assert_eq!(sin8(64 - theta), sin8(64 + theta), "{theta}");
+        assert_eq!(
+            sin8(theta) as i16 - 128,
+            128 - sin8(theta + 128) as i16,
+            "{theta}"
+        );
+    }
+}
+
+#[test]
+fn scale8_boundaries() {
+    assert_eq!(scale8(255, 255), 255);
+    assert_eq!(scale8(0, 255), 0);
+    assert_eq!(scale8(255, 0), 0);
+    assert_eq!(scale8(128, 128), 64);
+    for value in 0..=255u8 {
+        assert_eq!(scale8(value, 255), value);
+        assert_eq!(scale8(value, 0), 0);
+    }
+    assert_eq!(scale16(65535, 65535), 65535);
+    assert_eq!(scale16(65535, 0), 0);
+    assert_eq!(scale16(1000, 32767), 500);
+}
+
+#[test]
+fn saturating() {
+    assert_eq!(qadd8(200, 100), 255);
+    assert_eq!(qadd8(20, 10), 30);
+    assert_eq!(qsub8(10, 20), 0);
+    assert_eq!(qsub8(20, 10), 10);
+}
+
+#[test]
+fn lerp8_ends() {
+    for (a, b) in [(0, 255), (255, 0), (10, 200), (77, 77)] {
+        assert_eq!(lerp8(a, b, 0), a);
+        assert_eq!(lerp8(a, b, 255), b);
+        let middle = lerp8(a, b, 128) as i16;
+        assert!((middle - (a as i16 + b as i16) / 2).abs() <= 1, "{a} {b}");
+    }
+}
+
+#[test]
+fn beats() {
+    // 60 beats a minute is one beat a second.
+    assert_eq!(beat8(60, 0), 0);
+    assert_eq!(beat8(60, 250), 64);
+    assert_eq!(beat8(60, 500), 128);
+    assert_eq!(beat8(60, 1000), 0);
+    assert_eq!(beat16(120, 250), 32768);
+    // Long uptimes do not overflow.
+    assert_eq!(beat8(60, 49 * 24 * 3_600_000 + 500), 128);
+
+    assert_eq!(beatsin8(60, 10, 20, 0), 15);
+    for millis in (0..2000).step_by(7) {
+        let value = beatsin8(60, 10, 20, millis);
+        assert!((10..=20).contains(&value), "{value} at {millis}");
+    }
+}
+
+#[test]
+fn hsv8_primaries() {
+    assert_eq!(hsv8(0, 255, 255), RGB8::new(255, 0, 0));
+    assert_eq!(hsv8(86, 255, 255), RGB8::new(0, 255, 0));
+    assert_eq!(hsv8(172, 255, 255), RGB8::new(0, 0, 255));
+    assert_eq!(hsv8(0, 0, 200), RGB8::new(200, 200, 200));
+    assert_eq!(hsv8(123, 255, 0), RGB8::new(0, 0, 0));
+}
+
+#[test]
+fn hsv8_close_to_float() {
+    for hue in 0..=255u8 {
+        let RGB8 { r, g, b } = hsv8(hue, 255, 255);
+        // Fully saturated: one channel is off, one is full.
+        assert_eq!(r.min(g).min(b), 0, "{hue}");
+        assert_eq!(r.max(g).max(b), 255, "{hue}");
+        // The rising or falling channel follows the hue within a few steps.
+        let sector = hue as f32 * 6.0 / 256.0;
+        let ramp = (
+            (sector.fract() * 255.0) as i16,
+            255 - (sector.fract() * 255.0) as i16,
+        );
+        let middle = r as i16 + g as i16 + b as i16 - 255;
+        let expected = if (sector as u8) % 2 == 0 {
+            ramp.0
+        } else {
+            ramp.1
+        };
+        assert!(
+            (middle - expected).abs() <= 12,
+            "{hue}: {middle} vs {expected}"
+        );
+    }
+}
+
+#[test]
+fn q16() {
+    let half = Q16::from_ratio(1, 2);
+    assert_eq!(half, Q16::from_f32(0.5));
+    assert_eq!((half * Q16::from_int(3)).to_f32(), 1.5);
+    assert_eq!((Q16::ONE - Q16::from_f32(2.25)).floor(), -2);
+    assert_eq!(Q16::from_f32(-1.25).fract(), Q16::from_f32(0.75));
+    assert_eq!(Q16::from_u8(255).0, 65535);
+    assert_eq!((-half).abs(), half);
+    // sin(PI * 0.5) is the top of the wave.
+    assert_eq!(sin8(half.half_turns8()), 255);
+}
@@ -0,0 +1,215 @@
+//! The HTTP/JSON control API, fed raw request bytes as a client sends them.
+
+mod common;
+
+use led_effects::http_api::{Method, ParseError, Request};
+use smart_leds::RGB8;
+
+use common::{controller, get, post, serve, serve_in_chunks, NUM_LEDS};
+
+#[test]
+fn parse() {
+    let request = Request::parse(b"GET /state?verbose=1 HTTP/1.1\r\nHost: led\r\n\r\n").unwrap();
+    assert_eq!(request.method, Method::Get);
+    assert_eq!(request.path, "/state");
+    assert!(request.body.is_empty());
+
+    let bytes = post("/brightness", r#"{"brightness":12}"#);
+    let request = Request::parse(&bytes).unwrap();
+    assert_eq!(request.method, Method::Post);
+    assert_eq!(request.body, br#"{"brightness":12}"#);
+
+    let request = Request::parse(b"DELETE /effect HTTP/1.1\r\n\r\n").unwrap();
+    assert_eq!(request.method, Method::Other);
+}
+
+#[test]
+fn parse_incomplete() {
+    let bytes = post("/brightness", r#"{"brightness":12}"#);
+    let head_end = bytes.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+    for len in [0, 10, head_end - 1, head_end, bytes.len() - 1] {
+        let error = Request::parse(&bytes[..len]).err();
+        assert_eq!(error, Some(ParseError::Incomplete), "{len} bytes");
+    }
+    // Header names are not case sensitive.
+    let request = Request::parse(b"POST /effect HTTP/1.1\r\ncontent-length: 2\r\n\r\n{}").unwrap();
+    assert_eq!(request.body, b"{}");
+}
+
+#[test]
+fn parse_malformed() {
+    let malformed = |bytes: &[u8]| Request::parse(bytes).err();
+    assert_eq!(malformed(b"\r\n\r\n"), Some(ParseError::Malformed));
+    assert_eq!(malformed(b"GET\r\n\r\n"), Some(ParseError::Malformed));
+    assert_eq!(
+        malformed(b"POST /effect HTTP/1.1\r\nContent-Length: ten\r\n\r\n"),
+        Some(ParseError::Malformed)
+    );
+    assert_eq!(
+        malformed(b"GET /\xFF HTTP/1.1\r\n\r\n"),
+        Some(ParseError::Malformed)
+    );
+}
+
+#[test]
+fn parse_oversized_body() {
+    let malformed = |length: &str| {
+        let request = format!("POST /effect HTTP/1.1\r\nContent-Length: {length}\r\n\r\n{{}}");
+        Request::parse(request.as_bytes()).err()
+    };
+    // Lengths that overflow the body end, on the host and on the device.
+    assert_eq!(
+        malformed("18446744073709551615"),
+        Some(ParseError::Malformed)
+    );
+    assert_eq!(malformed("4294967295"), Some(ParseError::Malformed));
+    // A body that cannot fit in the request buffer is never complete.
+    assert_eq!(malformed("1024"), Some(ParseError::Malformed));
+    assert_eq!(malformed("900"), Some(ParseError::Incomplete));
+
+    let mut controller = controller();
+    let request = b"POST /effect HTTP/1.1\r\nContent-Length: 18446744073709551615\r\n\r\n";
+    let (status, _) = serve(&mut controller, request);
+    assert_eq!(status, 400);
+}
+
+#[test]
+fn effects_and_state() {
+    let mut controller = controller();
+    let (status, body) = serve(&mut controller, &get("/effects"));
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        r#"["Solid Color","PoliceDot","PoliceTrail","Drogen","Rocket"]"#
+    );
+
+    let (status, body) = serve(&mut controller, &get("/state"));
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        r#"{"effect":"Solid Color","brightness":255,"parameters":{"red":255.0,"green":0.0,"blue":0.0,"white":0.0}}"#
+    );
+}
+
+#[test]
+fn set_effect_and_brightness() {
+    let mut controller = controller();
+    let (status, body) = serve(&mut controller, &post("/effect", r#"{"name":"PoliceDot"}"#));
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        r#"{"effect":"PoliceDot","brightness":255,"parameters":{"speed":1.0,"size":2.0}}"#
+    );
+
+    let (status, body) = serve(
+        &mut controller,
+        &post("/brightness", r#"{"brightness":40}"#),
+    );
+    assert_eq!(status, 200);
+    assert!(body.contains(r#""brightness":40"#), "{body}");
+    assert_eq!(controller.brightness(), 40);
+
+    let (status, body) = serve(&mut controller, &post("/effect", r#"{"name":"Strobe"}"#));
+    assert_eq!(
+        (status, body.as_str()),
+        (404, r#"{"error":"unknown effect"}"#)
+    );
+    assert_eq!(controller.current_effect_name(), Ok("PoliceDot"));
+}
+
+#[test]
+fn parameters() {
+    let mut controller = controller();
+    serve(
+        &mut controller,
+        &post("/effect", r#"{"name":"PoliceTrail"}"#),
+    );
+    let (status, body) = serve(&mut controller, &get("/effect/parameters"));
+    assert_eq!(status, 200);
+    assert_eq!(body, r#"{"speed":1.0,"size":2.0,"trail_length":8.0}"#);
+
+    let set = post("/effect/parameters", r#"{"name":"speed","value":0.5}"#);
+    let (status, body) = serve(&mut controller, &set);
+    assert_eq!(status, 200);
+    assert_eq!(body, r#"{"speed":0.5,"size":2.0,"trail_length":8.0}"#);
+
+    let set = post("/effect/parameters", r#"{"name":"colour","value":1}"#);
+    let (status, body) = serve(&mut controller, &set);
+    assert_eq!(
+        (status, body.as_str()),
+        (404, r#"{"error":"unknown parameter"}"#)
+    );
+}
+
+#[test]
+fn sizes_are_limited_to_the_strip() {
+    let mut controller = controller();
+    let mut leds = [RGB8::default(); NUM_LEDS];
+    for effect in ["PoliceDot", "PoliceTrail"] {
+        let select = format!(r#"{{"name":"{effect}"}}"#);
+        serve(&mut controller, &post("/effect", &select));
+        for parameter in ["size", "trail_length"] {
+            let set = format!(r#"{{"name":"{parameter}","value":1e30}}"#);
+            serve(&mut controller, &post("/effect/parameters", &set));
+        }
+        let (_, body) = serve(&mut controller, &get("/effect/parameters"));
+        assert!(body.contains(r#""size":16.0"#), "{body}");
+        for _ in 0..40 {
+            controller.render(0.03, &mut leds);
+        }
+    }
+}
+
+#[test]
+fn errors() {
+    let mut controller = controller();
+    let (status, body) = serve(
+        &mut controller,
+        &post("/brightness", r#"{"brightness":300}"#),
+    );
+    assert_eq!(
+        (status, body.as_str()),
+        (400, r#"{"error":"invalid json"}"#)
+    );
+    let (status, body) = serve(&mut controller, &post("/effect", "name=Rocket"));
+    assert_eq!(
+        (status, body.as_str()),
+        (400, r#"{"error":"invalid json"}"#)
+    );
+
+    let (status, body) = serve(&mut controller, &post("/effects", "{}"));
+    assert_eq!(
+        (status, body.as_str()),
+        (405, r#"{"error":"method not allowed"}"#)
+    );
+    let (status, body) = serve(&mut controller, &get("/effect"));
+    assert_eq!(status, 405, "{body}");
+    let (status, body) = serve(&mut controller, &get("/favicon.ico"));
+    assert_eq!((status, body.as_str()), (404, r#"{"error":"not found"}"#));
+
+    let (status, body) = serve(&mut controller, b"GET\r\n\r\n");
+    assert_eq!((status, body.as_str()), (400, ""));
+}
+
+#[test]
+fn request_in_pieces() {
+    let request = post("/effect", r#"{"name":"Rocket"}"#);
+    for chunk in [1, 7, 64] {
+        let mut controller = controller();
+        let (status, body) = serve_in_chunks(&mut controller, &request, chunk);
+        assert_eq!(status, 200, "{chunk} byte reads");
+        assert!(body.starts_with(r#"{"effect":"Rocket""#), "{body}");
+    }
+}
+
+#[test]
+fn without_effects() {
+    let mut controller = led_effects::controller::EffectController::<NUM_LEDS>::new();
+    let (status, body) = serve(&mut controller, &get("/state"));
+    assert_eq!(
+        (status, body.as_str()),
+        (500, r#"{"error":"no effects registered"}"#)
+    );
+    let (status, body) = serve(&mut controller, &get("/effects"));
+    assert_eq!((status, body.as_str()), (200, "[]"));
+}
@@ -0,0 +1,162 @@
+//! Discovery, state and command payloads of the MQTT light, the commands as
+//! Home Assistant's JSON schema light sends them.
+
+mod common;
+
+use led_effects::controller::EffectController;
+use led_effects::mqtt::{self, apply_command, CommandError, Topics};
+use led_effects::registry;
+use smart_leds::RGB8;
+
+use common::{controller, NUM_LEDS};
+
+const MAC: [u8; 6] = [0x24, 0x6F, 0x28, 0x0A, 0xB1, 0xC2];
+
+fn state(controller: &EffectController<NUM_LEDS>) -> String {
+    let mut out = [0; 256];
+    let len = mqtt::state(controller, &mut out).unwrap();
+    String::from_utf8(out[..len].to_vec()).unwrap()
+}
+
+#[test]
+fn node_id_and_topics() {
+    let node_id = mqtt::node_id(MAC);
+    assert_eq!(node_id, "led-effects_246f280ab1c2");
+    let topics = Topics::new(&node_id);
+    assert_eq!(
+        topics.config,
+        "homeassistant/light/led-effects_246f280ab1c2/config"
+    );
+    assert_eq!(topics.command, "led-effects/led-effects_246f280ab1c2/set");
+    assert_eq!(topics.state, "led-effects/led-effects_246f280ab1c2/state");
+    assert_eq!(
+        topics.availability,
+        "led-effects/led-effects_246f280ab1c2/availability"
+    );
+    // Devices differing in any byte get their own id.
+    let other = mqtt::node_id([0x24, 0x6F, 0x28, 0x0A, 0xB1, 0xC3]);
+    assert_ne!(node_id, other);
+    let other = mqtt::node_id([0x30, 0xAE, 0xA4, 0x0A, 0xB1, 0xC2]);
+    assert_ne!(node_id, other);
+    assert_eq!(mqtt::node_id([0xFF; 6]), "led-effects_ffffffffffff");
+}
+
+#[test]
+fn discovery_config() {
+    let controller = controller();
+    let node_id = mqtt::node_id(MAC);
+    let topics = Topics::new(&node_id);
+    let mut out = [0; 1024];
+    let len =
+        mqtt::discovery_config(&controller, &node_id, "LED Strip", &topics, &mut out).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&out[..len]).unwrap(),
+        concat!(
+            r#"{"name":null,"unique_id":"led-effects_246f280ab1c2","schema":"json","#,
+            r#""command_topic":"led-effects/led-effects_246f280ab1c2/set","#,
+            r#""state_topic":"led-effects/led-effects_246f280ab1c2/state","#,
+            r#""availability_topic":"led-effects/led-effects_246f280ab1c2/availability","#,
+            r#""brightness":true,"effect":true,"#,
+            r#""effect_list":["Solid Color","PoliceDot","PoliceTrail","Drogen","Rocket"],"#,
+            r#""supported_color_modes":["rgb"],"#,
+            r#""device":{"identifiers":["led-effects_246f280ab1c2"],"name":"LED Strip","model":"led-effects"}}"#
+        )
+    );
+    // Too small a buffer is an error, not a truncated config.
+    assert!(
+        mqtt::discovery_config(&controller, &node_id, "LED Strip", &topics, &mut out[..64])
+            .is_err()
+    );
+}
+
+#[test]
+fn state_messages() {
+    let mut controller = controller();
+    assert_eq!(
+        state(&controller),
+        r#"{"state":"ON","brightness":255,"effect":"Solid Color","color_mode":"rgb","color":{"r":255,"g":0,"b":0}}"#
+    );
+    controller.set_on(false);
+    controller.set_effect_by_name("Rocket").unwrap();
+    assert_eq!(
+        state(&controller),
+        r#"{"state":"OFF","brightness":255,"effect":"Rocket"}"#
+    );
+}
+
+#[test]
+fn commands() {
+    let mut controller = controller();
+    apply_command(&mut controller, br#"{"state":"OFF"}"#).unwrap();
+    assert!(!controller.is_on());
+    apply_command(&mut controller, br#"{"state":"ON","brightness":77}"#).unwrap();
+    assert!(controller.is_on());
+    assert_eq!(controller.brightness(), 77);
+
+    apply_command(&mut controller, br#"{"state":"ON","effect":"PoliceTrail"}"#).unwrap();
+    assert_eq!(controller.current_effect_name(), Ok("PoliceTrail"));
+
+    // Picking a colour in the more-info dialog switches to a colour effect.
+    let payload = br#"{"state":"ON","color":{"r":255,"g":147,"b":41}}"#;
+    apply_command(&mut controller, payload).unwrap();
+    assert_eq!(controller.current_effect_name(), Ok("Solid Color"));
+    assert_eq!(controller.color(), Some(RGB8::new(255, 147, 41)));
+
+    // Fields Home Assistant sends that the light does not use are ignored.
+    let payload = br#"{"state":"ON","brightness":200,"transition":2.0}"#;
+    apply_command(&mut controller, payload).unwrap();
+    assert_eq!(controller.brightness(), 200);
+}
+
+#[test]
+fn bad_commands_change_nothing() {
+    let mut controller = controller();
+    controller.set_effect_by_name("PoliceDot").unwrap();
+    let check = |controller: &EffectController<NUM_LEDS>| {
+        assert!(controller.is_on());
+        assert_eq!(controller.brightness(), 255);
+        assert_eq!(controller.current_effect_name(), Ok("PoliceDot"));
+    };
+
+    let payload = br#"{"state":"OFF","brightness":10,"effect":"Strobe"}"#;
+    assert_eq!(
+        apply_command(&mut controller, payload),
+        Err(CommandError::UnknownEffect)
+    );
+    check(&controller);
+
+    let payload = br#"{"state":"TOGGLE","brightness":10}"#;
+    assert_eq!(
+        apply_command(&mut controller, payload),
+        Err(CommandError::InvalidJson)
+    );
+    check(&controller);
+
+    // Rocket has no colour to set.
+    let payload = br#"{"state":"OFF","effect":"Rocket","color":{"r":0,"g":0,"b":255}}"#;
+    assert_eq!(
+        apply_command(&mut controller, payload),
+        Err(CommandError::NoColorEffect)
+    );
+    check(&controller);
+
+    assert_eq!(
+        apply_command(&mut controller, b"ON"),
+        Err(CommandError::InvalidJson)
+    );
+    check(&controller);
+}
+
+#[test]
+fn color_without_color_effects() {
+    let mut controller = EffectController::<NUM_LEDS>::new();
+    controller
+        .register(registry::find("Rocket").unwrap())
+        .unwrap();
+    let payload = br#"{"state":"OFF","color":{"r":0,"g":0,"b":255}}"#;
+    assert_eq!(
+        apply_command(&mut controller, payload),
+        Err(CommandError::NoColorEffect)
+    );
+    assert!(controller.is_on());
+}
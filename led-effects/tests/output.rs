@@ -0,0 +1,172 @@
+//! Host tests of the chipset encodings, run with `just led-test`.
+
+use led_effects::output::{
+    extract_white, pulse_code, Chipset, ColorOrder, OutputConfig, OutputError, WhiteMode, RGBW8,
+};
+use smart_leds::{White, RGB8, RGBW};
+
+const RED: RGB8 = RGB8::new(0xFF, 0x00, 0x00);
+
+#[test]
+fn color_orders() {
+    let color = RGB8::new(1, 2, 3);
+    assert_eq!(ColorOrder::Rgb.arrange(color), [1, 2, 3]);
+    assert_eq!(ColorOrder::Rbg.arrange(color), [1, 3, 2]);
+    assert_eq!(ColorOrder::Grb.arrange(color), [2, 1, 3]);
+    assert_eq!(ColorOrder::Gbr.arrange(color), [2, 3, 1]);
+    assert_eq!(ColorOrder::Brg.arrange(color), [3, 1, 2]);
+    assert_eq!(ColorOrder::Bgr.arrange(color), [3, 2, 1]);
+}
+
+#[test]
+fn white_extraction() {
+    let white = extract_white(RGB8::new(200, 150, 100));
+    assert_eq!(
+        white,
+        RGBW {
+            r: 100,
+            g: 50,
+            b: 0,
+            a: White(100)
+        }
+    );
+}
+
+#[test]
+fn temperature_white() {
+    let warm = RGB8::new(255, 169, 87);
+    let mode = WhiteMode::Temperature(warm);
+    // A colour the white LED makes on its own moves entirely to white.
+    let white = mode.extract(warm);
+    assert_eq!((white.r, white.g, white.b, white.a.0), (0, 0, 0, 255));
+    // Blue is the limit, the red and green above the warm white are left.
+    let white = mode.extract(RGB8::new(200, 150, 50));
+    assert_eq!(white.a.0, 146);
+    assert_eq!((white.r, white.g, white.b), (54, 54, 1));
+    assert_eq!(mode.combine(white), RGB8::new(200, 150, 50));
+    // Without blue in the LED no blue limits it.
+    let mode = WhiteMode::Temperature(RGB8::new(255, 128, 0));
+    assert_eq!(mode.extract(RGB8::new(255, 128, 7)).a.0, 255);
+}
+
+#[test]
+fn rgbw_on_rgb_chipset() {
+    let config = OutputConfig::new(Chipset::Ws2812, ColorOrder::Rgb);
+    let rgbw: RGBW8 = RGBW {
+        r: 10,
+        g: 0,
+        b: 250,
+        a: White(20),
+    };
+    let mut mixed = [0; 25];
+    let mut expected = [0; 25];
+    config.encode_rmt([rgbw], 80, &mut mixed).unwrap();
+    config
+        .encode_rmt([RGB8::new(30, 20, 255)], 80, &mut expected)
+        .unwrap();
+    assert_eq!(mixed, expected);
+}
+
+#[test]
+fn ws2812_pulses() {
+    let config = OutputConfig::new(Chipset::Ws2812, ColorOrder::Grb);
+    let mut out = [0xAAAA_AAAA; 26];
+    let len = config.encode_rmt([RED], 80, &mut out).unwrap();
+    assert_eq!(len, config.rmt_len(1));
+
+    // 0.4 us high, 0.85 us low and 0.8 us high, 0.45 us low at 12.5 ns per tick.
+    let zero = pulse_code(32, 68);
+    let one = pulse_code(64, 36);
+    assert_eq!(zero, 0x0044_8020);
+    let mut expected = [zero; 25];
+    expected[8..16].fill(one);
+    expected[24] = 0;
+    assert_eq!(&out[..len], &expected);
+    assert_eq!(out[25], 0xAAAA_AAAA);
+}
+
+#[test]
+fn ws2811_slow_pulses() {
+    let config = OutputConfig::new(Chipset::Ws2811, ColorOrder::Rgb);
+    let mut out = [0; 25];
+    config.encode_rmt([RED], 80, &mut out).unwrap();
+    assert_eq!(out[0], pulse_code(96, 104));
+    assert_eq!(out[8], pulse_code(40, 160));
+}
+
+#[test]
+fn sk6812_rgbw_pulses() {
+    let config = OutputConfig::new(Chipset::Sk6812Rgbw, ColorOrder::Grb);
+    let mut out = [0; 33];
+    let len = config
+        .encode_rmt([RGB8::new(0x80, 0x80, 0x81)], 80, &mut out)
+        .unwrap();
+    assert_eq!(len, 33);
+
+    let zero = pulse_code(24, 72);
+    let one = pulse_code(48, 48);
+    // G and R are all white, B keeps its lowest bit, W is 0x80.
+    assert!(out[..23].iter().all(|&code| code == zero));
+    assert_eq!(out[23], one);
+    assert_eq!(out[24], one);
+    assert!(out[25..32].iter().all(|&code| code == zero));
+    assert_eq!(out[32], 0);
+}
+
+#[test]
+fn rmt_errors() {
+    let config = OutputConfig::new(Chipset::Ws2812, ColorOrder::Grb);
+    let mut out = [0; 24];
+    assert_eq!(
+        config.encode_rmt([RED], 80, &mut out),
+        Err(OutputError::BufferTooSmall)
+    );
+    let spi = OutputConfig::new(Chipset::Apa102, ColorOrder::Bgr);
+    assert_eq!(
+        spi.encode_rmt([RED], 80, &mut out),
+        Err(OutputError::WrongInterface)
+    );
+}
+
+#[test]
+fn apa102_frame() {
+    let config = OutputConfig::new(Chipset::Apa102, ColorOrder::Bgr);
+    let mut out = [0; 16];
+    let len = config.encode_spi([RED, RGB8::new(1, 2, 3)], &mut out).unwrap();
+    assert_eq!(len, config.spi_len(2));
+    assert_eq!(
+        &out[..len],
+        &[
+            0x00, 0x00, 0x00, 0x00, // start frame
+            0xFF, 0x00, 0x00, 0xFF, // red
+            0xFF, 0x03, 0x02, 0x01, // (1, 2, 3)
+            0xFF, 0xFF, 0xFF, 0xFF, // end frame
+        ]
+    );
+}
+
+#[test]
+fn sk9822_frame() {
+    let config = OutputConfig::new(Chipset::Sk9822, ColorOrder::Bgr);
+    let mut out = [0xAA; 96];
+    let len = config.encode_spi([RED; 20], &mut out).unwrap();
+    // Start frame, 20 LEDs, reset frame and 2 bytes to push 20 LEDs through.
+    assert_eq!(len, 4 + 80 + 4 + 2);
+    assert_eq!(len, config.spi_len(20));
+    assert_eq!(&out[len - 6..len], &[0; 6]);
+}
+
+#[test]
+fn spi_errors() {
+    let config = OutputConfig::new(Chipset::Ws2812, ColorOrder::Grb);
+    let mut out = [0; 16];
+    assert_eq!(
+        config.encode_spi([RED], &mut out),
+        Err(OutputError::WrongInterface)
+    );
+    let spi = OutputConfig::new(Chipset::Apa102, ColorOrder::Bgr);
+    assert_eq!(
+        spi.encode_spi([RED; 4], &mut out),
+        Err(OutputError::BufferTooSmall)
+    );
+}
@@ -0,0 +1,248 @@
+//! Adalight and TPM2 byte streams, as sent by Prismatik and Jinx!, fed to
+//! the decoders the firmware reads the stream UART with.
+
+use led_effects::adalight::AdalightDecoder;
+use led_effects::dmx::DmxReceiver;
+use led_effects::tpm2::Tpm2Decoder;
+use smart_leds::RGB8;
+
+/// Adalight frame for three LEDs: red, green and blue.
+const ADALIGHT: [u8; 15] = [
+    b'A', b'd', b'a', 0x00, 0x02, 0x57, 0xFF, 0x00, 0x00, 0x00, 0xFF, 0x00, 0x00, 0x00, 0xFF,
+];
+
+/// TPM2 data frame for the same three LEDs.
+const TPM2: [u8; 14] = [
+    0xC9, 0xDA, 0x00, 0x09, 0xFF, 0x00, 0x00, 0x00, 0xFF, 0x00, 0x00, 0x00, 0xFF, 0x36,
+];
+
+const RGB: [RGB8; 3] = [
+    RGB8::new(0xFF, 0, 0),
+    RGB8::new(0, 0xFF, 0),
+    RGB8::new(0, 0, 0xFF),
+];
+
+/// Adalight frame with `colors`.
+fn adalight(colors: &[RGB8]) -> Vec<u8> {
+    let [high, low] = (colors.len() as u16 - 1).to_be_bytes();
+    let mut bytes = vec![b'A', b'd', b'a', high, low, high ^ low ^ 0x55];
+    bytes.extend(colors.iter().flat_map(|c| [c.r, c.g, c.b]));
+    bytes
+}
+
+/// TPM2 data frame with `colors`.
+fn tpm2(colors: &[RGB8]) -> Vec<u8> {
+    let [high, low] = (colors.len() as u16 * 3).to_be_bytes();
+    let mut bytes = vec![0xC9, 0xDA, high, low];
+    bytes.extend(colors.iter().flat_map(|c| [c.r, c.g, c.b]));
+    bytes.push(0x36);
+    bytes
+}
+
+/// A gradient over `count` LEDs, the way an ambient light sends them.
+fn gradient(count: usize) -> Vec<RGB8> {
+    (0..count)
+        .map(|i| {
+            let step = (i * 255 / count) as u8;
+            RGB8::new(step, 255 - step, step / 2)
+        })
+        .collect()
+}
+
+struct Decoders {
+    dmx: DmxReceiver,
+    adalight: AdalightDecoder<4>,
+    tpm2: Tpm2Decoder<4>,
+}
+
+/// Frames completed by each decoder.
+#[derive(Debug, Default, PartialEq)]
+struct Frames {
+    dmx: usize,
+    adalight: usize,
+    tpm2: usize,
+}
+
+impl Decoders {
+    fn new() -> Self {
+        Self {
+            dmx: DmxReceiver::new(),
+            adalight: AdalightDecoder::new(),
+            tpm2: Tpm2Decoder::new(),
+        }
+    }
+
+    /// Feeds every byte to every decoder, as the firmware does.
+    fn feed(&mut self, bytes: &[u8]) -> Frames {
+        let mut frames = Frames::default();
+        for &byte in bytes {
+            frames.dmx += self.dmx.feed(byte) as usize;
+            frames.adalight += self.adalight.feed(byte) as usize;
+            frames.tpm2 += self.tpm2.feed(byte) as usize;
+        }
+        frames
+    }
+}
+
+fn count<F: FnMut(u8) -> bool>(bytes: &[u8], mut feed: F) -> usize {
+    bytes.iter().filter(|&&byte| feed(byte)).count()
+}
+
+#[test]
+fn adalight_frame() {
+    assert_eq!(ADALIGHT.to_vec(), adalight(&RGB));
+    let mut decoder = AdalightDecoder::<4>::new();
+    assert_eq!(count(&ADALIGHT, |byte| decoder.feed(byte)), 1);
+    assert_eq!(&decoder.pixels()[..3], RGB);
+    assert_eq!(decoder.pixels()[3], RGB8::default());
+
+    // Split anywhere, the frame is the same.
+    for split in 0..ADALIGHT.len() {
+        let (first, second) = ADALIGHT.split_at(split);
+        assert_eq!(count(first, |byte| decoder.feed(byte)), 0);
+        assert_eq!(count(second, |byte| decoder.feed(byte)), 1);
+        assert_eq!(&decoder.pixels()[..3], RGB);
+    }
+}
+
+#[test]
+fn adalight_checksum() {
+    let mut decoder = AdalightDecoder::<4>::new();
+    let mut bytes = ADALIGHT;
+    bytes[5] ^= 0x01;
+    assert_eq!(count(&bytes, |byte| decoder.feed(byte)), 0);
+    assert_eq!(decoder.pixels(), [RGB8::default(); 4]);
+}
+
+#[test]
+fn adalight_resync() {
+    let mut decoder = AdalightDecoder::<4>::new();
+    // Line noise, a partial magic and a repeated start.
+    let mut bytes = b"\x00\xFFAdAAd".to_vec();
+    bytes.extend(ADALIGHT);
+    assert_eq!(count(&bytes, |byte| decoder.feed(byte)), 1);
+    assert_eq!(&decoder.pixels()[..3], RGB);
+}
+
+#[test]
+fn adalight_short_and_long_frames() {
+    let mut decoder = AdalightDecoder::<4>::new();
+    let one = [RGB8::new(1, 2, 3)];
+    assert_eq!(count(&adalight(&one), |byte| decoder.feed(byte)), 1);
+    assert_eq!(decoder.pixels()[0], one[0]);
+    // Colours from the previous frame are not left behind.
+    assert_eq!(decoder.pixels()[1..], [RGB8::default(); 3]);
+
+    // More LEDs than kept: the rest is read and dropped.
+    let colors = gradient(300);
+    assert_eq!(count(&adalight(&colors), |byte| decoder.feed(byte)), 1);
+    assert_eq!(decoder.pixels(), &colors[..4]);
+    assert_eq!(count(&ADALIGHT, |byte| decoder.feed(byte)), 1);
+}
+
+#[test]
+fn tpm2_frame() {
+    assert_eq!(TPM2.to_vec(), tpm2(&RGB));
+    let mut decoder = Tpm2Decoder::<4>::new();
+    assert_eq!(count(&TPM2, |byte| decoder.feed(byte)), 1);
+    assert_eq!(&decoder.pixels()[..3], RGB);
+
+    for split in 0..TPM2.len() {
+        let (first, second) = TPM2.split_at(split);
+        assert_eq!(count(first, |byte| decoder.feed(byte)), 0);
+        assert_eq!(count(second, |byte| decoder.feed(byte)), 1);
+        assert_eq!(&decoder.pixels()[..3], RGB);
+    }
+}
+
+#[test]
+fn tpm2_block_start_and_end() {
+    let mut decoder = Tpm2Decoder::<4>::new();
+    // Noise before the start byte is skipped.
+    let mut bytes = vec![0x00, 0x36, 0xDA];
+    bytes.extend(TPM2);
+    assert_eq!(count(&bytes, |byte| decoder.feed(byte)), 1);
+
+    // A wrong end byte drops the frame.
+    let mut bytes = TPM2;
+    bytes[13] = 0x00;
+    assert_eq!(count(&bytes, |byte| decoder.feed(byte)), 0);
+
+    // Command frames, here a 1 byte "set brightness", are not data.
+    let command = [0xC9, 0xC0, 0x00, 0x01, 0x80, 0x36];
+    assert_eq!(count(&command, |byte| decoder.feed(byte)), 0);
+    assert_eq!(count(&TPM2, |byte| decoder.feed(byte)), 1);
+
+    // An empty data frame clears the strip.
+    let empty = [0xC9, 0xDA, 0x00, 0x00, 0x36];
+    assert_eq!(count(&empty, |byte| decoder.feed(byte)), 1);
+    assert_eq!(decoder.pixels(), [RGB8::default(); 4]);
+}
+
+#[test]
+fn tpm2_size_mismatch() {
+    let mut decoder = Tpm2Decoder::<4>::new();
+    // The header announces four LEDs but only three follow: the end byte
+    // and the start of the next frame are read as colours.
+    let mut bytes = TPM2;
+    bytes[3] = 0x0C;
+    let mut stream = bytes.to_vec();
+    stream.extend(TPM2);
+    stream.extend(TPM2);
+    assert_eq!(count(&stream, |byte| decoder.feed(byte)), 1);
+    assert_eq!(&decoder.pixels()[..3], RGB);
+
+    // More data than announced: the byte after the data is not the end.
+    let mut bytes = TPM2;
+    bytes[3] = 0x06;
+    assert_eq!(count(&bytes, |byte| decoder.feed(byte)), 0);
+    assert_eq!(count(&TPM2, |byte| decoder.feed(byte)), 1);
+}
+
+/// Feeds each protocol to fresh decoders: after a false start inside
+/// another protocol's colours a decoder waits for the frame size it read,
+/// up to 64 KiB for TPM2, before it looks for a frame again.
+#[test]
+fn no_crosstalk() {
+    let mut stream = Vec::new();
+    for count in 1..=64 {
+        stream.extend(adalight(&gradient(count)));
+    }
+    let frames = Decoders::new().feed(&stream);
+    assert_eq!(
+        frames,
+        Frames {
+            adalight: 64,
+            ..Frames::default()
+        }
+    );
+
+    let mut stream = Vec::new();
+    for count in 1..=64 {
+        stream.extend(tpm2(&gradient(count)));
+    }
+    let frames = Decoders::new().feed(&stream);
+    assert_eq!(
+        frames,
+        Frames {
+            tpm2: 64,
+            ..Frames::default()
+        }
+    );
+
+    // Full DMX universes as a DMX USB Pro sends them.
+    let mut stream = Vec::new();
+    for frame in 0..16 {
+        stream.extend([0x7E, 0x06, 0x01, 0x02, 0x00]);
+        stream.extend((0..512).map(|channel| (channel * frame) as u8));
+        stream.push(0xE7);
+    }
+    let frames = Decoders::new().feed(&stream);
+    assert_eq!(
+        frames,
+        Frames {
+            dmx: 16,
+            ..Frames::default()
+        }
+    );
+}
@@ -0,0 +1,147 @@
+//! The WLED JSON API subset, with request bodies as the WLED app and Home
+//! Assistant's WLED integration send them.
+
+mod common;
+
+use led_effects::controller::EffectController;
+use smart_leds::RGB8;
+
+use common::{controller, get, post, serve, NUM_LEDS};
+
+#[test]
+fn state_and_info() {
+    let mut controller = controller();
+    let (status, body) = serve(&mut controller, &get("/json/state"));
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        concat!(
+            r#"{"on":true,"bri":255,"transition":0,"ps":-1,"pl":-1,"seg":[{"id":0,"start":0,"#,
+            r#""stop":16,"len":16,"on":true,"bri":255,"fx":0,"pal":0,"#,
+            r#""col":[[255,0,0],[0,0,0],[0,0,0]]}]}"#
+        )
+    );
+
+    let (status, body) = serve(&mut controller, &get("/json/info"));
+    assert_eq!(status, 200);
+    assert!(
+        body.contains(r#""leds":{"count":16,"rgbw":false,"maxseg":1}"#),
+        "{body}"
+    );
+    assert!(body.contains(r#""fxcount":5,"palcount":6"#), "{body}");
+    assert!(body.contains(r#""mac":"246f280ab1c2""#), "{body}");
+
+    let (status, body) = serve(&mut controller, &get("/json/pal"));
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        r#"["Default","Rainbow","Party","Lava","Ocean","Forest"]"#
+    );
+
+    let (status, body) = serve(&mut controller, &get("/json"));
+    assert_eq!(status, 200);
+    assert!(body.starts_with(r#"{"state":{"on":true"#), "{body}");
+    assert!(body.ends_with(r#""palettes":["Default","Rainbow","Party","Lava","Ocean","Forest"]}"#));
+}
+
+#[test]
+fn power_and_brightness() {
+    let mut controller = controller();
+    let (status, body) = serve(&mut controller, &post("/json/state", r#"{"on":false}"#));
+    assert_eq!((status, body.as_str()), (200, r#"{"success":true}"#));
+    assert!(!controller.is_on());
+
+    // The brightness slider of the WLED app, asking for the new state back.
+    let body = r#"{"on":true,"bri":64,"v":true}"#;
+    let (status, body) = serve(&mut controller, &post("/json/state", body));
+    assert_eq!(status, 200);
+    assert!(body.starts_with(r#"{"on":true,"bri":64,"#), "{body}");
+    assert_eq!(controller.brightness(), 64);
+}
+
+#[test]
+fn effect_and_palette() {
+    let mut controller = controller();
+    let body = r#"{"seg":[{"id":0,"fx":4,"pal":2}]}"#;
+    let (status, _) = serve(&mut controller, &post("/json/state", body));
+    assert_eq!(status, 200);
+    assert_eq!(controller.current_effect_name(), Ok("Rocket"));
+    assert_eq!(controller.palette_index(), 2);
+
+    // Segments other than 0 do not exist, updates to them are dropped.
+    let body = r#"{"seg":[{"id":1,"fx":1}]}"#;
+    serve(&mut controller, &post("/json/state", body));
+    assert_eq!(controller.current_effect_name(), Ok("Rocket"));
+
+    // Speed and intensity sliders are not mapped and are ignored.
+    let body = r#"{"on":true,"bri":200,"transition":7,"seg":[{"fx":2,"sx":128,"ix":200}]}"#;
+    let (status, _) = serve(&mut controller, &post("/json/state", body));
+    assert_eq!(status, 200);
+    assert_eq!(controller.current_effect_name(), Ok("PoliceTrail"));
+}
+
+#[test]
+fn colors() {
+    let mut controller = controller();
+    // The colour wheel of the WLED app sends all three segment colours.
+    let body = r#"{"seg":[{"col":[[255,160,0],[0,0,0],[0,0,0]]}]}"#;
+    serve(&mut controller, &post("/json/state", body));
+    assert_eq!(controller.color(), Some(RGB8::new(255, 160, 0)));
+
+    // A colour on its own switches to an effect that shows it.
+    controller.set_effect_by_name("Drogen").unwrap();
+    let body = r#"{"seg":[{"col":[[0,0,255,0]]}]}"#;
+    serve(&mut controller, &post("/json/state", body));
+    assert_eq!(controller.current_effect_name(), Ok("Solid Color"));
+    assert_eq!(controller.color(), Some(RGB8::new(0, 0, 255)));
+}
+
+#[test]
+fn effect_with_color() {
+    let mut controller = controller();
+    // Drogen takes no colour, the effect asked for wins.
+    let body = r#"{"seg":[{"fx":3,"col":[[255,0,0]]}]}"#;
+    let (status, _) = serve(&mut controller, &post("/json/state", body));
+    assert_eq!(status, 200);
+    assert_eq!(controller.current_effect_name(), Ok("Drogen"));
+
+    let body = r#"{"seg":[{"fx":0,"col":[[0,255,0]]}]}"#;
+    serve(&mut controller, &post("/json/state", body));
+    assert_eq!(controller.current_effect_name(), Ok("Solid Color"));
+    assert_eq!(controller.color(), Some(RGB8::new(0, 255, 0)));
+}
+
+#[test]
+fn bad_updates_change_nothing() {
+    let mut controller = controller();
+    let check = |controller: &EffectController<NUM_LEDS>| {
+        assert!(controller.is_on());
+        assert_eq!(controller.brightness(), 255);
+        assert_eq!(controller.current_effect_name(), Ok("Solid Color"));
+        assert_eq!(controller.palette_index(), 0);
+    };
+
+    let body = r#"{"on":false,"bri":1,"seg":[{"fx":5}]}"#;
+    let (status, body) = serve(&mut controller, &post("/json/state", body));
+    assert_eq!(
+        (status, body.as_str()),
+        (404, r#"{"error":"unknown effect"}"#)
+    );
+    check(&controller);
+
+    let body = r#"{"on":false,"seg":[{"fx":1,"pal":6}]}"#;
+    let (status, body) = serve(&mut controller, &post("/json/state", body));
+    assert_eq!(
+        (status, body.as_str()),
+        (404, r#"{"error":"unknown palette"}"#)
+    );
+    check(&controller);
+
+    // Toggling with "t" is not supported.
+    let (status, body) = serve(&mut controller, &post("/json/state", r#"{"on":"t"}"#));
+    assert_eq!(
+        (status, body.as_str()),
+        (400, r#"{"error":"invalid json"}"#)
+    );
+    check(&controller);
+}